@@ -1,24 +1,24 @@
-#![feature(const_option)]
-
 use robius_authentication::{
-    AndroidText, BiometricStrength, Context, Policy, PolicyBuilder, Text, WindowsText,
+    policy, text, AndroidText, BiometricStrength, Context, LinuxText, Policy, Text,
 };
 
-const POLICY: Policy = PolicyBuilder::new()
-    .biometrics(Some(BiometricStrength::Strong))
-    .password(true)
-    .watch(true)
-    .build()
-    .unwrap();
+const POLICY: Policy = policy! {
+    biometrics: Some(BiometricStrength::Strong),
+    password: true,
+    watch: true,
+};
 
-const TEXT: Text = Text {
+const TEXT: Text = text! {
     android: AndroidText {
         title: "Title",
         subtitle: None,
         description: None,
     },
     apple: "authenticate",
-    windows: WindowsText::new("Title", "Description").unwrap(),
+    windows: ("Title", "Description"),
+    linux: LinuxText {
+        message: "Authentication is required to continue",
+    },
 };
 
 fn main() {
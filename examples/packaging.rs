@@ -0,0 +1,48 @@
+//! Prints the packaging metadata for an app with two actions.
+//!
+//! Run with `cargo run --example packaging -- <android|apple|linux>`.
+
+use robius_authentication::packaging::{
+    validate_polkit_policy, Action, Authorization, Declaration, Defaults,
+};
+
+const UNLOCK_VAULT: &str = "com.example.app.unlock-vault";
+const EXPORT_KEYS: &str = "com.example.app.export-keys";
+
+const DECLARATION: Declaration = Declaration {
+    vendor: "Example",
+    vendor_url: Some("https://example.com"),
+    icon_name: None,
+    usage_description: "Unlock your vault with Face ID",
+    actions: &[
+        Action {
+            id: UNLOCK_VAULT,
+            description: "Unlock the vault",
+            message: "Authentication is required to unlock the vault",
+            defaults: Defaults::all(Authorization::AuthSelfKeep),
+            annotations: &[],
+        },
+        Action {
+            id: EXPORT_KEYS,
+            description: "Export private keys",
+            message: "Authentication is required to export private keys",
+            defaults: Defaults::all(Authorization::AuthAdmin),
+            // Exporting keys also allows unlocking the vault.
+            annotations: &[("org.freedesktop.policykit.imply", UNLOCK_VAULT)],
+        },
+    ],
+};
+
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("android") => print!("{}", DECLARATION.android_manifest()),
+        Some("apple") => print!("{}", DECLARATION.info_plist()),
+        Some("linux") => {
+            let policy = DECLARATION.polkit_policy();
+            validate_polkit_policy(&policy, &[UNLOCK_VAULT, EXPORT_KEYS])
+                .expect("generated an invalid policy");
+            print!("{policy}");
+        }
+        _ => eprintln!("usage: packaging <android|apple|linux>"),
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::Instant;
+
+use crate::BiometricStrength;
+
+/// A successful authentication, as returned by
+/// [`Context::authenticate`](crate::Context::authenticate).
+///
+/// Systems differ in what they report about how the user authenticated, so
+/// anything that isn't known is `None`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Authentication {
+    /// How the user authenticated.
+    ///
+    /// If the policy required several methods (see
+    /// [`PolicyBuilder::require_all`](crate::PolicyBuilder::require_all)),
+    /// this is the last one.
+    pub method: Option<AuthenticationMethod>,
+    /// Every method the user authenticated with, in order.
+    ///
+    /// This only has more than one method if the policy required several.
+    pub factors: Vec<AuthenticationMethod>,
+    /// The strength class of the biometric the user authenticated with.
+    ///
+    /// This is only reported on Android.
+    pub strength: Option<BiometricStrength>,
+    /// The name of the account whose credentials were verified.
+    ///
+    /// This can differ from the current user, e.g. if polkit required an
+    /// administrator to authenticate.
+    pub account: Option<String>,
+    /// When authentication finished.
+    pub timestamp: Instant,
+}
+
+impl Authentication {
+    /// Returns an authentication that finished now, e.g. in an
+    /// [`Authenticator`](crate::Authenticator).
+    #[inline]
+    pub fn new(
+        method: Option<AuthenticationMethod>,
+        strength: Option<BiometricStrength>,
+        account: Option<String>,
+    ) -> Self {
+        Self {
+            method,
+            factors: method.into_iter().collect(),
+            strength,
+            account,
+            timestamp: Instant::now(),
+        }
+    }
+
+    /// Combines the authentication with the following one, when several
+    /// methods are required.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub(crate) fn then(mut self, next: Self) -> Self {
+        self.factors.extend(next.factors);
+        Self {
+            method: next.method,
+            factors: self.factors,
+            strength: self.strength.or(next.strength),
+            account: next.account.or(self.account),
+            timestamp: next.timestamp,
+        }
+    }
+}
+
+/// A way of authenticating.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthenticationMethod {
+    /// A biometric, e.g. a fingerprint or face.
+    Biometric,
+    /// A password, PIN, or pattern.
+    Credential,
+    /// A paired watch.
+    Watch,
+}
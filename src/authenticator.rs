@@ -0,0 +1,145 @@
+use std::fmt::Debug;
+#[cfg(feature = "async")]
+use std::{future::Future, pin::Pin};
+
+use crate::{
+    event::EventHandler, sys, AuthEvent, Authentication, Availability, Policy, RawContext, Result,
+    Text,
+};
+
+/// A future returned by [`Authenticator::authenticate`].
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A backend that verifies the user's identity.
+///
+/// A [`Context`](crate::Context) authenticates using the system's backend
+/// ([`SystemAuthenticator`]) by default, or any other backend using
+/// [`Context::with_authenticator`](crate::Context::with_authenticator). On
+/// Linux, each of the system's services can also be used on its own (see
+/// [`linux`](crate::linux)).
+///
+/// Backends are used by the context in the same way regardless of where they
+/// are implemented, so a custom backend, e.g. for a hardware token:
+/// - receives the [`Policy`] being authenticated, and should fail with
+///   [`Error::Unavailable`](crate::Error::Unavailable) if it doesn't allow the
+///   backend's method,
+/// - isn't called while a previous authentication can be reused (see
+///   [`PolicyBuilder::reuse_duration`](crate::PolicyBuilder::reuse_duration)),
+/// - is cancelled once the policy's timeout expires, and
+/// - lets a [`Fallback`](crate::Fallback) try its next policy by failing with
+///   one of the errors that allow falling back.
+pub trait Authenticator: Debug + Send + Sync {
+    /// Authenticates the user, blocking until they're done.
+    fn blocking_authenticate(&self, request: AuthenticationRequest<'_>) -> Result<Authentication>;
+
+    /// Authenticates the user.
+    ///
+    /// The default implementation calls
+    /// [`blocking_authenticate`](Self::blocking_authenticate), which blocks
+    /// the executor while the user authenticates.
+    #[cfg(feature = "async")]
+    fn authenticate<'a>(
+        &'a self,
+        request: AuthenticationRequest<'a>,
+    ) -> BoxFuture<'a, Result<Authentication>> {
+        Box::pin(async move { self.blocking_authenticate(request) })
+    }
+
+    /// Returns whether authentication using `policy` can succeed, without
+    /// prompting the user.
+    fn availability(&self, policy: &Policy) -> Result<Availability>;
+
+    /// Returns whether the backend can be used in the current session, before
+    /// any policy is known, e.g. whether its service is running.
+    ///
+    /// A [`Registry`](crate::Registry) selects the first backend whose probe
+    /// succeeds. The default implementation always succeeds.
+    fn probe(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Cancels the request in flight, if any, which should then fail with
+    /// [`Error::AppCanceled`](crate::Error::AppCanceled).
+    ///
+    /// Requests started afterwards must not be affected. This may be called
+    /// from any thread.
+    fn cancel(&self);
+}
+
+/// An authentication request passed to an [`Authenticator`].
+#[derive(Clone, Copy, Debug)]
+pub struct AuthenticationRequest<'a> {
+    pub(crate) text: Text<'a, 'a, 'a, 'a, 'a, 'a, 'a>,
+    pub(crate) policy: &'a Policy,
+    pub(crate) events: &'a EventHandler,
+}
+
+impl<'a> AuthenticationRequest<'a> {
+    /// Returns the text the prompt should show.
+    #[inline]
+    pub fn text(&self) -> Text<'a, 'a, 'a, 'a, 'a, 'a, 'a> {
+        self.text
+    }
+
+    /// Returns the policy being authenticated.
+    #[inline]
+    pub fn policy(&self) -> &'a Policy {
+        self.policy
+    }
+
+    /// Reports the request's progress to the context's event handler, if it
+    /// has one.
+    #[inline]
+    pub fn report(&self, event: AuthEvent) {
+        self.events.emit(event);
+    }
+}
+
+/// The system's backend, which [`Context::new`](crate::Context::new)
+/// authenticates using.
+///
+/// See the [crate documentation](crate) for the services used on each target.
+#[derive(Debug)]
+pub struct SystemAuthenticator {
+    inner: sys::Context,
+}
+
+impl SystemAuthenticator {
+    #[inline]
+    pub fn new(raw: RawContext) -> Self {
+        Self {
+            inner: sys::Context::new(raw),
+        }
+    }
+}
+
+impl Authenticator for SystemAuthenticator {
+    #[inline]
+    fn blocking_authenticate(&self, request: AuthenticationRequest<'_>) -> Result<Authentication> {
+        self.inner
+            .blocking_authenticate(request.text, &request.policy.inner, request.events)
+    }
+
+    #[inline]
+    #[cfg(feature = "async")]
+    fn authenticate<'a>(
+        &'a self,
+        request: AuthenticationRequest<'a>,
+    ) -> BoxFuture<'a, Result<Authentication>> {
+        Box::pin(
+            self.inner
+                .authenticate(request.text, &request.policy.inner, request.events),
+        )
+    }
+
+    #[inline]
+    fn availability(&self, policy: &Policy) -> Result<Availability> {
+        self.inner.availability(&policy.inner)
+    }
+
+    #[inline]
+    fn cancel(&self) {
+        self.inner.handle().cancel();
+    }
+}
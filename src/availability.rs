@@ -0,0 +1,60 @@
+use crate::{Error, Result};
+
+/// Whether authentication using a policy can succeed, as reported by
+/// [`Context::availability`](crate::Context::availability).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Availability {
+    pub status: AvailabilityStatus,
+    /// The kind of biometry supported by the device, if known.
+    ///
+    /// This is reported even if the policy can't currently use it, e.g.
+    /// because the user hasn't enrolled any biometric identities.
+    pub biometry: Option<BiometryKind>,
+}
+
+impl Availability {
+    /// Returns whether authentication can succeed.
+    #[inline]
+    pub const fn is_available(&self) -> bool {
+        matches!(self.status, AvailabilityStatus::Available)
+    }
+
+    /// Converts the result of a backend's check, keeping errors that don't
+    /// describe why authentication is unavailable.
+    pub(crate) fn from_result(result: Result<()>, biometry: Option<BiometryKind>) -> Result<Self> {
+        let status = match result {
+            Ok(()) => AvailabilityStatus::Available,
+            Err(Error::NotEnrolled) => AvailabilityStatus::NotEnrolled,
+            Err(Error::PasscodeNotSet) => AvailabilityStatus::PasscodeNotSet,
+            Err(Error::Unavailable) => AvailabilityStatus::Unavailable,
+            Err(Error::DisabledByPolicy) => AvailabilityStatus::DisabledByPolicy,
+            Err(e) => return Err(e),
+        };
+        Ok(Self { status, biometry })
+    }
+}
+
+/// Why authentication can't succeed, if it can't.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AvailabilityStatus {
+    /// Authentication can succeed.
+    Available,
+    /// The policy requires biometrics, but the user hasn't enrolled any
+    /// biometric identities.
+    NotEnrolled,
+    /// The policy requires a passcode, but none is set on the device.
+    PasscodeNotSet,
+    /// The device doesn't support the authentication methods allowed by the
+    /// policy.
+    Unavailable,
+    /// The system's configuration forbids authenticating using the policy.
+    DisabledByPolicy,
+}
+
+/// A kind of biometry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BiometryKind {
+    Fingerprint,
+    Face,
+    Iris,
+}
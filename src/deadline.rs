@@ -0,0 +1,60 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{AuthenticationHandle, Error, Result};
+
+/// How often a request is cancelled again after its deadline, in case it
+/// hadn't started yet.
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Cancels a context's request in flight once a timeout expires, unless it
+/// is dropped first.
+pub(crate) struct Deadline {
+    expired: Arc<AtomicBool>,
+    /// Wakes the timer when dropped.
+    _stop: mpsc::Sender<()>,
+}
+
+impl Deadline {
+    pub(crate) fn start(handle: AuthenticationHandle, timeout: Duration) -> Self {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let expired = Arc::new(AtomicBool::new(false));
+
+        let flag = expired.clone();
+        thread::spawn(move || {
+            if stopped.recv_timeout(timeout) != Err(RecvTimeoutError::Timeout) {
+                return;
+            }
+            flag.store(true, Ordering::SeqCst);
+            // The backend may not have registered the request yet, so it's
+            // cancelled until the caller stops waiting for it.
+            loop {
+                handle.cancel();
+                if stopped.recv_timeout(RETRY_INTERVAL) != Err(RecvTimeoutError::Timeout) {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            expired,
+            _stop: stop,
+        }
+    }
+
+    /// Returns the request's result, reporting it as timed out if it was
+    /// cancelled because the deadline expired.
+    pub(crate) fn finish<T>(self, result: Result<T>) -> Result<T> {
+        match result {
+            Err(Error::AppCanceled) if self.expired.load(Ordering::SeqCst) => Err(Error::Timeout),
+            result => result,
+        }
+    }
+}
@@ -1,8 +1,23 @@
+use std::fmt;
+
+use crate::{messages, ErrorMessage};
+
 /// The result of an authentication operation.
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// An error produced during authentication.
+///
+/// Errors reported by the system that don't correspond to any other variant
+/// are returned as [`Error::Platform`], which carries the system's error code.
+/// Errors that do correspond to another variant, e.g. an `LAError` of
+/// `userCancel`, which is returned as [`Error::UserCanceled`], don't carry the
+/// system's code or message.
+///
+/// The `is_*` methods classify errors without matching on every variant, and
+/// [`message`](Error::message) returns a message that can be shown to the user
+/// (see [`Catalog`](crate::Catalog) for other languages).
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     // TODO: Reexport jni::errors::Error
     // TODO: Remove target cfg
@@ -19,15 +34,14 @@ pub enum Error {
     Unavailable,
     /// The user canceled authentication.
     UserCanceled,
+    /// The app canceled authentication, e.g. using
+    /// [`AuthenticationHandle::cancel`](crate::AuthenticationHandle::cancel).
+    AppCanceled,
+    /// Authentication didn't finish before the policy's timeout (see
+    /// [`PolicyBuilder::timeout`](crate::PolicyBuilder::timeout)) expired.
+    Timeout,
 
     // Apple-specific errors
-    /// The app canceled authentication.
-    ///
-    /// This error can occur on:
-    /// - [Apple]
-    ///
-    /// [Apple]: https://developer.apple.com/documentation/localauthentication/laerror/laerrorappcancel
-    AppCanceled,
     /// The system canceled authentication.
     ///
     /// This error can occur on:
@@ -40,6 +54,7 @@ pub enum Error {
     ///
     /// This error can occur on:
     /// - [Apple]
+    /// - Linux
     ///
     /// [Apple]: https://developer.apple.com/documentation/localauthentication/laerror/laerrorbiometrydisconnected
     BiometryDisconnected,
@@ -55,6 +70,7 @@ pub enum Error {
     ///
     /// This error can occur on:
     /// - [Apple]
+    /// - Linux
     ///
     /// [Apple]: https://developer.apple.com/documentation/localauthentication/laerror/laerrorbiometrynotenrolled
     NotEnrolled,
@@ -62,6 +78,8 @@ pub enum Error {
     ///
     /// This error can occur on:
     /// - [Apple]
+    /// - Linux, if there is no authentication agent or terminal to show the
+    ///   prompt
     ///
     /// [Apple]: https://developer.apple.com/documentation/localauthentication/laerror/laerrornotinteractive
     NotInteractive,
@@ -77,6 +95,17 @@ pub enum Error {
     ///
     /// [Apple]: https://developer.apple.com/documentation/localauthentication/laerror/laerrorinvaliddimensions
     InvalidDimensions,
+    /// The user chose to authenticate using a fallback method instead, e.g.
+    /// by tapping "Enter Password".
+    ///
+    /// When authenticating using a [`Fallback`](crate::Fallback), the next
+    /// policy is tried.
+    ///
+    /// This error can occur on:
+    /// - [Apple]
+    ///
+    /// [Apple]: https://developer.apple.com/documentation/localauthentication/laerror/laerroruserfallback
+    UserFallback,
     /// A passcode isn’t set on the device.
     ///
     /// This error can occur on:
@@ -87,21 +116,24 @@ pub enum Error {
 
     // Android-specific errors
     UpdateRequired,
-    Timeout,
 
     // Windows-specific errors
     /// The biometric verifier device is performing an operation and is
     /// unavailable.
     ///
     /// This error can occur on:
+    /// - Linux
     /// - [Windows]
     ///
     /// [Windows]: https://learn.microsoft.com/en-us/uwp/api/windows.security.credentials.ui.userconsentverificationresult
     Busy,
-    /// Group policy has disabled the biometric verifier device.
+    /// The system's configuration forbids authenticating, e.g. group policy has
+    /// disabled the biometric verifier device, or polkit doesn't authorize the
+    /// policy's action.
     ///
     /// This error can occur on:
     /// - [Windows]
+    /// - Linux, when checking [availability](crate::Context::availability)
     ///
     /// [Windows]: https://learn.microsoft.com/en-us/uwp/api/windows.security.credentials.ui.userconsentverificationresult
     DisabledByPolicy,
@@ -113,10 +145,194 @@ pub enum Error {
     /// [Windows]: https://learn.microsoft.com/en-us/uwp/api/windows.security.credentials.ui.userconsentverificationresult
     NotConfigured,
 
+    /// The system reported an error that doesn't correspond to any of the
+    /// other variants.
+    ///
+    /// This is the only variant that carries the system's error code.
+    Platform(PlatformError),
     /// An unknown error occurred.
     Unknown,
 }
 
+impl Error {
+    /// Returns whether authenticating again may succeed without the user
+    /// changing any settings, e.g. after an incorrect password or while the
+    /// fingerprint reader is busy.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Authentication
+                | Self::Timeout
+                | Self::SystemCanceled
+                | Self::BiometryDisconnected
+                | Self::WatchNotAvailable
+                | Self::Busy
+        )
+    }
+
+    /// Returns whether the user chose not to authenticate.
+    pub fn is_user_cancellation(&self) -> bool {
+        matches!(self, Self::UserCanceled)
+    }
+
+    /// Returns whether authentication can't succeed until the user or an
+    /// administrator changes the device's settings, e.g. by enrolling a
+    /// fingerprint or setting a passcode.
+    pub fn is_configuration_problem(&self) -> bool {
+        matches!(
+            self,
+            Self::NotPaired
+                | Self::NotEnrolled
+                | Self::PasscodeNotSet
+                | Self::UpdateRequired
+                | Self::DisabledByPolicy
+                | Self::NotConfigured
+        )
+    }
+
+    /// Returns the error reported by the system, if it doesn't correspond to
+    /// any other variant.
+    ///
+    /// Returns `None` for every other variant, including those the system's
+    /// errors are mapped to, as they don't keep the system's code.
+    pub fn platform(&self) -> Option<&PlatformError> {
+        match self {
+            Self::Platform(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Returns the ID of the error's user-facing message in a
+    /// [`Catalog`](crate::Catalog), e.g. `not-enrolled`.
+    ///
+    /// Errors reported by the system that the user can't do anything about
+    /// share the `platform` message.
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            #[cfg(target_os = "android")]
+            Self::Java(_) => "platform",
+            Self::Authentication => "authentication",
+            Self::Exhausted => "exhausted",
+            Self::Unavailable => "unavailable",
+            Self::UserCanceled => "user-canceled",
+            Self::AppCanceled => "app-canceled",
+            Self::Timeout => "timeout",
+            Self::SystemCanceled => "system-canceled",
+            Self::BiometryDisconnected => "biometry-disconnected",
+            Self::NotPaired => "not-paired",
+            Self::NotEnrolled => "not-enrolled",
+            Self::NotInteractive => "not-interactive",
+            Self::WatchNotAvailable => "watch-not-available",
+            Self::InvalidDimensions => "invalid-dimensions",
+            Self::UserFallback => "user-fallback",
+            Self::PasscodeNotSet => "passcode-not-set",
+            Self::UpdateRequired => "update-required",
+            Self::Busy => "busy",
+            Self::DisabledByPolicy => "disabled-by-policy",
+            Self::NotConfigured => "not-configured",
+            Self::Platform(_) => "platform",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// Returns the error's user-facing message and remediation hint in
+    /// English.
+    ///
+    /// Use a [`Catalog`](crate::Catalog) for other locales.
+    #[inline]
+    pub fn message(&self) -> ErrorMessage {
+        messages::english(self.message_id())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            #[cfg(target_os = "android")]
+            Self::Java(error) => return write!(f, "JNI call failed: {error}"),
+            Self::Authentication => "the user failed to provide valid credentials",
+            Self::Exhausted => "too many failed attempts",
+            Self::Unavailable => "the requested authentication method is unavailable",
+            Self::UserCanceled => "the user canceled authentication",
+            Self::AppCanceled => "the app canceled authentication",
+            Self::Timeout => "authentication timed out",
+            Self::SystemCanceled => "the system canceled authentication",
+            Self::BiometryDisconnected => "the biometric accessory isn't connected",
+            Self::NotPaired => "no biometric accessory is paired",
+            Self::NotEnrolled => "the user has no enrolled biometric identities",
+            Self::NotInteractive => "the authentication prompt can't be displayed",
+            Self::WatchNotAvailable => "authentication using the paired watch failed",
+            Self::InvalidDimensions => "the authentication prompt has invalid dimensions",
+            Self::UserFallback => "the user chose to authenticate using another method",
+            Self::PasscodeNotSet => "a passcode isn't set on the device",
+            Self::UpdateRequired => "a security update is required",
+            Self::Busy => "the biometric device is busy",
+            Self::DisabledByPolicy => "authentication is disabled by policy",
+            Self::NotConfigured => "no biometric device is configured for the user",
+            Self::Platform(error) => return write!(f, "system error: {error}"),
+            Self::Unknown => "an unknown error occurred",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(target_os = "android")]
+            Self::Java(error) => Some(error),
+            Self::Platform(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// An error reported by the system, with its code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PlatformError {
+    /// An [`LAError`] code, and the error's localized description, on Apple
+    /// targets.
+    ///
+    /// [`LAError`]: https://developer.apple.com/documentation/localauthentication/laerror/code
+    LocalAuthentication { code: isize, message: String },
+    /// A [`BiometricPrompt`] error code, on Android.
+    ///
+    /// [`BiometricPrompt`]: https://developer.android.com/reference/android/hardware/biometrics/BiometricPrompt
+    BiometricPrompt { code: i32 },
+    /// An `HRESULT`, and its message, on Windows.
+    Windows { code: i32, message: String },
+    /// A GLib error, e.g. from D-Bus, on Linux.
+    ///
+    /// D-Bus errors that GLib doesn't know have the `g-io-error-quark` domain,
+    /// and their message contains the D-Bus error's name.
+    GLib {
+        domain: String,
+        code: i32,
+        message: String,
+    },
+    /// A PAM status code, and its message, on Linux.
+    Pam { code: i32, message: String },
+}
+
+impl fmt::Display for PlatformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LocalAuthentication { code, message } => write!(f, "{message} (LAError {code})"),
+            Self::BiometricPrompt { code } => write!(f, "BiometricPrompt error {code}"),
+            Self::Windows { code, message } => write!(f, "{message} (HRESULT {code:#010x})"),
+            Self::GLib {
+                domain,
+                code,
+                message,
+            } => write!(f, "{message} ({domain} {code})"),
+            Self::Pam { code, message } => write!(f, "{message} (PAM status {code})"),
+        }
+    }
+}
+
+impl std::error::Error for PlatformError {}
+
 #[cfg(target_os = "android")]
 impl From<jni::errors::Error> for Error {
     fn from(value: jni::errors::Error) -> Self {
@@ -0,0 +1,48 @@
+use std::{fmt, sync::Arc};
+
+/// Progress reported while authenticating, before the final result.
+///
+/// Events are reported to the handler set using
+/// [`Context::set_event_handler`](crate::Context::set_event_handler), e.g. to
+/// show "Try again" hints while the prompt stays open.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuthEvent {
+    /// The user presented a biometric that wasn't recognized, and may try
+    /// again.
+    AttemptFailed,
+    /// The scan couldn't be used, and the user should scan their finger again.
+    RetryScan,
+    /// The user swiped their finger too quickly.
+    SwipeTooShort,
+    /// The user's finger wasn't centered on the reader.
+    FingerNotCentered,
+    /// The user should lift their finger from the reader and scan it again.
+    RemoveAndRetry,
+    /// A hint from the system to show to the user, e.g. "Move your finger
+    /// slightly".
+    Help(String),
+}
+
+/// The handler a context reports events to, if any.
+#[derive(Clone, Default)]
+pub(crate) struct EventHandler(Option<Arc<dyn Fn(AuthEvent) + Send + Sync>>);
+
+impl EventHandler {
+    pub(crate) fn new(handler: impl Fn(AuthEvent) + Send + Sync + 'static) -> Self {
+        Self(Some(Arc::new(handler)))
+    }
+
+    pub(crate) fn emit(&self, event: AuthEvent) {
+        if let Some(handler) = &self.0 {
+            handler(event);
+        }
+    }
+}
+
+impl fmt::Debug for EventHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EventHandler")
+            .field(&self.0.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{Authentication, Error, Policy};
+
+/// An ordered list of policies that are tried in turn until one of them can
+/// be used.
+///
+/// The next policy is tried if authenticating using the previous one fails
+/// with:
+/// - [`Error::Unavailable`], e.g. if there's no fingerprint reader,
+/// - [`Error::NotEnrolled`], if the user hasn't enrolled a biometric, or
+/// - [`Error::UserFallback`], if the user chose to authenticate differently.
+///
+/// Any other error, e.g. an incorrect password, fails authentication without
+/// trying the remaining policies.
+///
+/// Each policy selects the methods that are used, and on Linux also the
+/// backends. For example, on Linux the following first tries a policy that
+/// only allows biometrics, i.e. the user's fingerprint using fprintd, then
+/// one that only allows their password, which is verified using polkit, and
+/// finally one that verifies the password using the app's own `my-app-pin`
+/// PAM service instead, which asks for whatever its configuration requires:
+///
+/// ```
+/// use robius_authentication::{Fallback, PolicyBuilder};
+///
+/// # #[cfg(target_os = "linux")]
+/// # {
+/// let fallback = Fallback::new()
+///     .then(PolicyBuilder::new().password(false).build().unwrap())
+///     .then(PolicyBuilder::new().biometrics(None).build().unwrap())
+///     .then(
+///         PolicyBuilder::new()
+///             .biometrics(None)
+///             .pam_service("my-app-pin")
+///             .build()
+///             .unwrap(),
+///     );
+/// # }
+/// ```
+///
+/// These policies aren't valid on every target, e.g. Windows requires both
+/// biometrics and passwords to be allowed, in which case
+/// [`PolicyBuilder::build`](crate::PolicyBuilder::build) returns an error.
+///
+/// Policies are tried using
+/// [`Context::blocking_authenticate_with_fallback`](crate::Context::blocking_authenticate_with_fallback),
+/// which reports which of them succeeded.
+#[derive(Clone, Debug, Default)]
+pub struct Fallback {
+    policies: Vec<Policy>,
+}
+
+impl Fallback {
+    /// Returns an empty list of policies.
+    ///
+    /// Authenticating using an empty list fails with [`Error::Unavailable`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a policy that is tried if the previous ones can't be used.
+    #[inline]
+    #[must_use]
+    pub fn then(mut self, policy: Policy) -> Self {
+        self.policies.push(policy);
+        self
+    }
+
+    /// Returns the policies, in the order they are tried.
+    #[inline]
+    pub fn policies(&self) -> &[Policy] {
+        &self.policies
+    }
+
+    /// Returns whether the policy after the one that failed with `error`
+    /// should be tried.
+    pub(crate) fn falls_back(error: &Error) -> bool {
+        matches!(
+            error,
+            Error::Unavailable | Error::NotEnrolled | Error::UserFallback
+        )
+    }
+}
+
+/// A successful authentication using a [`Fallback`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FallbackAuthentication {
+    /// The index of the policy that succeeded.
+    pub step: usize,
+    /// How the user authenticated.
+    pub authentication: Authentication,
+}
@@ -5,31 +5,33 @@
 //! - Android: See below for additional steps.
 //! - Windows. Only supports devices that have biometric authentication
 //!   hardware. Password-only authentication is still a work in progress.
+//! - Linux: Uses [`fprintd`] for fingerprints and [`polkit`] otherwise. See
+//!   below for additional steps.
 //!
 //! # Examples
 //!
 //! ```no_run
-//! #![feature(const_option)]
-//!
 //! use robius_authentication::{
-//!     AndroidText, BiometricStrength, Context, Policy, PolicyBuilder, Text, WindowsText,
+//!     policy, text, AndroidText, BiometricStrength, Context, LinuxText, Policy, Text,
 //! };
 //!
-//! const POLICY: Policy = PolicyBuilder::new()
-//!     .biometrics(Some(BiometricStrength::Strong))
-//!     .password(true)
-//!     .watch(true)
-//!     .build()
-//!     .unwrap();
+//! const POLICY: Policy = policy! {
+//!     biometrics: Some(BiometricStrength::Strong),
+//!     password: true,
+//!     watch: true,
+//! };
 //!
-//! const TEXT: Text = Text {
+//! const TEXT: Text = text! {
 //!     android: AndroidText {
 //!         title: "Title",
 //!         subtitle: None,
 //!         description: None,
 //!     },
 //!     apple: "authenticate",
-//!     windows: WindowsText::new("Title", "Description").unwrap(),
+//!     windows: ("Title", "Description"),
+//!     linux: LinuxText {
+//!         message: "Authentication is required to continue",
+//!     },
 //! };
 //!
 //! Context::new(())
@@ -37,62 +39,375 @@
 //!     .expect("authentication failed");
 //! ```
 //!
+//! The [`policy!`] and [`text!`] macros fail to compile if the policy or text
+//! isn't valid for the current target. [`PolicyBuilder`] and [`TextBuf`] can be
+//! used to construct them at runtime instead.
+//!
 //! For more details about the prompt text see [`Text`].
 //!
+//! [`Context::new`] authenticates using the system's services described below.
+//! [`Context::with_authenticator`] can be used to authenticate using another
+//! [`Authenticator`] instead, e.g. a hardware token.
+//!
 //! # Android
 //!
 //! For authentication to work, the following must be added to
-//! `AndroidManifest.xml`:
+//! `AndroidManifest.xml` (see also [`packaging`]):
 //! ```xml
 //! <uses-permission android:name="android.permission.USE_BIOMETRIC" />
 //! ```
 //!
+//! # Linux
+//!
+//! Authentication is performed by asking [`polkit`] to authorize the
+//! policy's action (see [`PolicyBuilder::action_id`]), so the action must be
+//! declared in a policy file installed in `/usr/share/polkit-1/actions`. The
+//! [`packaging`] module can generate the file, e.g.:
+//! ```xml
+//! <?xml version="1.0" encoding="UTF-8"?>
+//! <!DOCTYPE policyconfig PUBLIC
+//!  "-//freedesktop//DTD PolicyKit Policy Configuration 1.0//EN"
+//!  "http://www.freedesktop.org/standards/PolicyKit/1/policyconfig.dtd">
+//! <policyconfig>
+//!   <action id="rs.robius.authentication.authenticate">
+//!     <description>Authenticate</description>
+//!     <message>Authentication is required</message>
+//!     <defaults>
+//!       <allow_any>auth_self</allow_any>
+//!       <allow_inactive>auth_self</allow_inactive>
+//!       <allow_active>auth_self</allow_active>
+//!     </defaults>
+//!   </action>
+//! </policyconfig>
+//! ```
+//!
+//! The prompt shown by the authentication agent uses the action's `<message>`
+//! from the policy file, rather than the text from [`LinuxText`], as polkit
+//! only accepts a message from callers running as root or as the action's
+//! owner.
+//!
+//! If polkit has no authentication agent to show the prompt, e.g. on minimal
+//! window managers or over SSH, an agent is registered for the process while
+//! authenticating, which asks for the password using the policy's [`Prompt`]
+//! (see [`PolicyBuilder::prompt`]). Without one, the password is read from the
+//! process's controlling terminal, if it has one. The reason for
+//! authenticating is printed first, and Ctrl-C cancels the prompt. If polkit
+//! isn't running at all, the password is verified using PAM instead.
+//!
+//! Alternatively, if the policy has a PAM service (see
+//! [`PolicyBuilder::pam_service`]), the password is always verified using PAM,
+//! which doesn't require a policy file or polkit.
+//!
+//! If the policy allows biometrics, the user's fingerprint is verified using
+//! the default reader known to [`fprintd`] first, while the reason for
+//! authenticating is shown using the policy's [`Prompt`] or the controlling
+//! terminal. If there is no reader, the user hasn't enrolled any fingerprints,
+//! or their fingerprint isn't recognized, and the policy allows passwords, the
+//! password is verified instead. Without a prompt or terminal to show the
+//! reason, the password is verified straight away if the policy allows it. If
+//! the policy requires both (see [`PolicyBuilder::require_all`]), the
+//! fingerprint is verified first, followed by the password using PAM.
+//!
+//! Each of these services can also be used on its own (see [`linux`]), or
+//! selected at runtime using [`Context::from_registry`], in which case it can
+//! be overridden using e.g. `ROBIUS_AUTH_BACKEND=tty` (see [`Registry`]).
+//!
 //! [`LAContext`]: https://developer.apple.com/documentation/localauthentication/lacontext
+//! [`fprintd`]: https://fprint.freedesktop.org/
 //! [`polkit`]: https://www.freedesktop.org/software/polkit/docs/latest/polkit.8.html
 
+mod authentication;
+mod authenticator;
+mod availability;
+mod deadline;
 mod error;
+mod event;
+mod fallback;
+#[cfg(target_os = "linux")]
+pub mod linux;
+mod macros;
+mod messages;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod packaging;
+mod prompt;
+mod registry;
+mod reuse;
 mod sys;
+mod target;
 mod text;
 
+use std::{borrow::Cow, sync::Arc, time::Duration};
+
+#[cfg(feature = "async")]
+pub use crate::authenticator::BoxFuture;
 pub use crate::{
-    error::{Error, Result},
-    text::{AndroidText, Text, WindowsText},
+    authentication::{Authentication, AuthenticationMethod},
+    authenticator::{AuthenticationRequest, Authenticator, SystemAuthenticator},
+    availability::{Availability, AvailabilityStatus, BiometryKind},
+    error::{Error, PlatformError, Result},
+    event::AuthEvent,
+    fallback::{Fallback, FallbackAuthentication},
+    messages::{Catalog, ErrorMessage},
+    prompt::Prompt,
+    registry::{Registry, Selection, SelectionReason, BACKEND_VAR},
+    target::{AndroidAuthenticators, ApplePolicy, LinuxPolicy, PolicyError, PolicyMapping, Target},
+    text::{
+        AndroidText, AndroidTextBuf, LinuxText, LinuxTextBuf, Text, TextBuf, WindowsText,
+        WindowsTextBuf,
+    },
 };
+use crate::{deadline::Deadline, event::EventHandler, reuse::ReuseCache};
 
 pub type RawContext = sys::RawContext;
 
 #[derive(Debug)]
 pub struct Context {
-    inner: sys::Context,
+    inner: Arc<dyn Authenticator>,
+    selection: Option<Selection>,
+    events: EventHandler,
+    reuse: ReuseCache,
 }
 
 impl Context {
+    /// Returns a context that authenticates using the system's backend
+    /// ([`SystemAuthenticator`]).
+    ///
+    /// The backend isn't affected by the environment or any config file; use
+    /// [`Context::from_registry`] to select it at runtime instead.
     #[inline]
     pub fn new(raw: RawContext) -> Self {
+        Self::with_authenticator(SystemAuthenticator::new(raw))
+    }
+
+    /// Returns a context that authenticates using the backend selected from
+    /// `registry` (see [`Registry`]).
+    ///
+    /// Fails with [`Error::Unavailable`] if none of the registry's backends
+    /// can be used.
+    #[inline]
+    pub fn from_registry(registry: Registry) -> Result<Self> {
+        let (inner, selection) = registry.select()?;
+        Ok(Self {
+            inner,
+            selection: Some(selection),
+            events: EventHandler::default(),
+            reuse: ReuseCache::default(),
+        })
+    }
+
+    /// Returns a context that authenticates using `authenticator` instead of
+    /// the system's backend.
+    #[inline]
+    pub fn with_authenticator(authenticator: impl Authenticator + 'static) -> Self {
         Self {
-            inner: sys::Context::new(raw),
+            inner: Arc::new(authenticator),
+            selection: None,
+            events: EventHandler::default(),
+            reuse: ReuseCache::default(),
         }
     }
 
+    /// Returns which backend the context authenticates using, and why it was
+    /// selected, if it was selected from a [`Registry`].
+    #[inline]
+    pub fn selection(&self) -> Option<&Selection> {
+        self.selection.as_ref()
+    }
+
+    /// Returns a context that authenticates using a scripted backend instead
+    /// of the system's, e.g. in an application's tests.
+    #[inline]
+    #[cfg(feature = "mock")]
+    pub fn mock(backend: &mock::MockBackend) -> Self {
+        Self::with_authenticator(backend.clone())
+    }
+
+    /// Sets the handler the context's authentication requests report their
+    /// progress to, replacing the previous one.
+    ///
+    /// The handler may be called from another thread while a request is in
+    /// flight. Events are reported by fingerprint readers on Linux, and by
+    /// the biometric prompt on Android.
+    #[inline]
+    pub fn set_event_handler(&mut self, handler: impl Fn(AuthEvent) + Send + Sync + 'static) {
+        self.events = EventHandler::new(handler);
+    }
+
     /// Authenticates using the provided policy and message.
     ///
-    /// Returns whether the authentication was successful.
+    /// Returns how the user authenticated, if the authentication was
+    /// successful.
     #[inline]
     #[cfg(feature = "async")]
     pub async fn authenticate(
         &self,
-        message: Text<'_, '_, '_, '_, '_, '_>,
+        message: Text<'_, '_, '_, '_, '_, '_, '_>,
         policy: &Policy,
-    ) -> Result<()> {
-        self.inner.authenticate(message, &policy.inner).await
+    ) -> Result<Authentication> {
+        if let Some(authentication) = self.reuse.get(policy) {
+            return Ok(authentication);
+        }
+        let deadline = self.deadline(policy);
+        let result = self.inner.authenticate(self.request(message, policy)).await;
+        let result = match deadline {
+            Some(deadline) => deadline.finish(result),
+            None => result,
+        };
+        self.reuse.update(policy, &result);
+        result
     }
 
     /// Authenticates using the provided policy and message.
     ///
-    /// Returns whether the authentication was successful.
+    /// Returns how the user authenticated, if the authentication was
+    /// successful.
     #[inline]
-    pub fn blocking_authenticate(&self, message: Text, policy: &Policy) -> Result<()> {
-        self.inner.blocking_authenticate(message, &policy.inner)
+    pub fn blocking_authenticate(&self, message: Text, policy: &Policy) -> Result<Authentication> {
+        if let Some(authentication) = self.reuse.get(policy) {
+            return Ok(authentication);
+        }
+        let deadline = self.deadline(policy);
+        let result = self
+            .inner
+            .blocking_authenticate(self.request(message, policy));
+        let result = match deadline {
+            Some(deadline) => deadline.finish(result),
+            None => result,
+        };
+        self.reuse.update(policy, &result);
+        result
+    }
+
+    /// Authenticates using each of the fallback's policies in turn, until one
+    /// succeeds or fails with an error that doesn't allow falling back (see
+    /// [`Fallback`]).
+    ///
+    /// Returns which policy succeeded and how the user authenticated. If none
+    /// of the policies could be used, the last policy's error is returned.
+    #[inline]
+    #[cfg(feature = "async")]
+    pub async fn authenticate_with_fallback(
+        &self,
+        message: Text<'_, '_, '_, '_, '_, '_, '_>,
+        fallback: &Fallback,
+    ) -> Result<FallbackAuthentication> {
+        let mut error = Error::Unavailable;
+        for (step, policy) in fallback.policies().iter().enumerate() {
+            match self.authenticate(message, policy).await {
+                Ok(authentication) => {
+                    return Ok(FallbackAuthentication {
+                        step,
+                        authentication,
+                    })
+                }
+                Err(e) if Fallback::falls_back(&e) => error = e,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(error)
+    }
+
+    /// Authenticates using each of the fallback's policies in turn, until one
+    /// succeeds or fails with an error that doesn't allow falling back (see
+    /// [`Fallback`]).
+    ///
+    /// Returns which policy succeeded and how the user authenticated. If none
+    /// of the policies could be used, the last policy's error is returned.
+    pub fn blocking_authenticate_with_fallback(
+        &self,
+        message: Text,
+        fallback: &Fallback,
+    ) -> Result<FallbackAuthentication> {
+        let mut error = Error::Unavailable;
+        for (step, policy) in fallback.policies().iter().enumerate() {
+            match self.blocking_authenticate(message, policy) {
+                Ok(authentication) => {
+                    return Ok(FallbackAuthentication {
+                        step,
+                        authentication,
+                    })
+                }
+                Err(e) if Fallback::falls_back(&e) => error = e,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(error)
+    }
+
+    /// Forgets the context's successful authentications, so that policies
+    /// with a reuse duration (see [`PolicyBuilder::reuse_duration`]) prompt
+    /// the user again, e.g. after they log out of the app.
+    #[inline]
+    pub fn forget_authentications(&self) {
+        self.reuse.clear();
+    }
+
+    /// Returns a handle that can cancel the context's authentication requests,
+    /// e.g. from another thread.
+    #[inline]
+    pub fn handle(&self) -> AuthenticationHandle {
+        AuthenticationHandle {
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Returns whether authentication using the provided policy can succeed,
+    /// without showing a prompt.
+    ///
+    /// This can be used to decide whether to offer authentication at all, e.g.
+    /// before showing an "Unlock with fingerprint" button. The system's
+    /// services may be queried, so this can block briefly.
+    ///
+    /// On Windows, authentication is always available, as the password is
+    /// verified using the credential prompt if Windows Hello isn't available.
+    #[inline]
+    pub fn availability(&self, policy: &Policy) -> Result<Availability> {
+        self.inner.availability(policy)
+    }
+
+    fn request<'a>(
+        &'a self,
+        text: Text<'a, 'a, 'a, 'a, 'a, 'a, 'a>,
+        policy: &'a Policy,
+    ) -> AuthenticationRequest<'a> {
+        AuthenticationRequest {
+            text,
+            policy,
+            events: &self.events,
+        }
+    }
+
+    /// Starts cancelling the context's request once the policy's timeout
+    /// expires, if it has one.
+    fn deadline(&self, policy: &Policy) -> Option<Deadline> {
+        policy
+            .timeout
+            .map(|timeout| Deadline::start(self.handle(), timeout))
+    }
+}
+
+/// A handle for cancelling a [`Context`]'s authentication requests.
+///
+/// Handles can be cloned and sent to other threads.
+#[derive(Clone, Debug)]
+pub struct AuthenticationHandle {
+    inner: Arc<dyn Authenticator>,
+}
+
+impl AuthenticationHandle {
+    /// Cancels the context's authentication request in flight, if any, and
+    /// dismisses its prompt.
+    ///
+    /// The request fails with [`Error::AppCanceled`]. Requests started
+    /// afterwards aren't affected.
+    ///
+    /// On Linux, a password being verified using PAM is only cancelled once the
+    /// [`Prompt`] returns. On Windows, the password prompt shown if Windows
+    /// Hello isn't set up can't be cancelled.
+    #[inline]
+    pub fn cancel(&self) {
+        self.inner.cancel();
     }
 }
 
@@ -103,15 +418,27 @@ impl Context {
 /// documentation][android-docs] for more details.
 ///
 /// [android-docs]: https://source.android.com/docs/security/features/biometric
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BiometricStrength {
     Strong,
     Weak,
 }
 
+/// The polkit action that is checked if the policy doesn't specify one.
+const DEFAULT_ACTION_ID: &str = "rs.robius.authentication.authenticate";
+
 #[derive(Debug)]
 pub struct PolicyBuilder {
-    inner: sys::PolicyBuilder,
+    biometrics: Option<BiometricStrength>,
+    password: bool,
+    watch: bool,
+    wrist_detection: bool,
+    action_id: &'static str,
+    prompt: Option<&'static dyn Prompt>,
+    pam_service: Option<&'static str>,
+    require_all: bool,
+    timeout: Option<Duration>,
+    reuse_duration: Option<Duration>,
 }
 
 impl Default for PolicyBuilder {
@@ -126,7 +453,16 @@ impl PolicyBuilder {
     #[inline]
     pub const fn new() -> Self {
         Self {
-            inner: sys::PolicyBuilder::new(),
+            biometrics: Some(BiometricStrength::Strong),
+            password: true,
+            watch: true,
+            wrist_detection: true,
+            action_id: DEFAULT_ACTION_ID,
+            prompt: None,
+            pam_service: None,
+            require_all: false,
+            timeout: None,
+            reuse_duration: None,
         }
     }
 
@@ -138,7 +474,8 @@ impl PolicyBuilder {
     #[must_use]
     pub const fn biometrics(self, strength: Option<BiometricStrength>) -> Self {
         Self {
-            inner: self.inner.biometrics(strength),
+            biometrics: strength,
+            ..self
         }
     }
 
@@ -146,9 +483,7 @@ impl PolicyBuilder {
     #[inline]
     #[must_use]
     pub const fn password(self, password: bool) -> Self {
-        Self {
-            inner: self.inner.password(password),
-        }
+        Self { password, ..self }
     }
 
     /// Sets whether the policy supports watch proximity authentication.
@@ -157,9 +492,7 @@ impl PolicyBuilder {
     #[inline]
     #[must_use]
     pub const fn watch(self, watch: bool) -> Self {
-        Self {
-            inner: self.inner.watch(watch),
-        }
+        Self { watch, ..self }
     }
 
     /// Sets whether the policy requires the watch to be on the user's wrist.
@@ -169,29 +502,282 @@ impl PolicyBuilder {
     #[must_use]
     pub const fn wrist_detection(self, wrist_detection: bool) -> Self {
         Self {
-            inner: self.inner.wrist_detection(wrist_detection),
+            wrist_detection,
+            ..self
         }
     }
 
-    /// Constructs the policy.
+    /// Sets the polkit action that is checked when authenticating.
     ///
-    /// Returns `None` if the specified configuration is not valid for the
-    /// current target.
+    /// Different actions can be given different implicit authorizations (e.g.
+    /// `auth_self` or `auth_admin_keep`) in the policy file declaring them.
+    /// Defaults to `rs.robius.authentication.authenticate`.
+    ///
+    /// This only has an effect on Linux.
     #[inline]
     #[must_use]
-    pub const fn build(self) -> Option<Policy> {
-        Some(Policy {
-            // TODO: feature(const_try)
-            inner: match self.inner.build() {
-                Some(inner) => inner,
-                None => return None,
-            },
-        })
+    pub const fn action_id(self, action_id: &'static str) -> Self {
+        Self { action_id, ..self }
+    }
+
+    /// Sets the prompt used to ask for the user's password when polkit has no
+    /// authentication agent to do so, or when it is verified using PAM.
+    ///
+    /// The prompt is shown the reason for authenticating before it is asked
+    /// for anything. Defaults to the process's controlling terminal.
+    ///
+    /// This only has an effect on Linux.
+    #[inline]
+    #[must_use]
+    pub const fn prompt(self, prompt: &'static dyn Prompt) -> Self {
+        Self {
+            prompt: Some(prompt),
+            ..self
+        }
+    }
+
+    /// Sets the PAM service used to verify the user's password, instead of
+    /// asking polkit.
+    ///
+    /// The service's configuration is read from `/etc/pam.d`, unless `service`
+    /// is an absolute path to the configuration file. If not set, PAM's `login`
+    /// service is only used if polkit isn't running.
+    ///
+    /// This only has an effect on Linux.
+    #[inline]
+    #[must_use]
+    pub const fn pam_service(self, service: &'static str) -> Self {
+        Self {
+            pam_service: Some(service),
+            ..self
+        }
+    }
+
+    /// Sets whether the user must authenticate using every method the policy
+    /// allows, one after the other, instead of any one of them.
+    ///
+    /// Authentication stops at the first method that fails. Defaults to
+    /// `false`.
+    ///
+    /// This is only supported on Linux, where the user's fingerprint is
+    /// verified using fprintd, followed by their password using PAM (see
+    /// [`PolicyBuilder::pam_service`]), and only if the policy allows both.
+    /// Otherwise, the policy isn't valid if this is set.
+    #[inline]
+    #[must_use]
+    pub const fn require_all(self, require_all: bool) -> Self {
+        Self {
+            require_all,
+            ..self
+        }
+    }
+
+    /// Sets how long authentication may take before it is cancelled.
+    ///
+    /// Once the timeout expires, the prompt is dismissed and authentication
+    /// fails with [`Error::Timeout`], as if it had been cancelled using an
+    /// [`AuthenticationHandle`], so the same limitations apply. Defaults to no
+    /// timeout.
+    #[inline]
+    #[must_use]
+    pub const fn timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Sets how long after the user authenticates the same context may return
+    /// that authentication again without prompting.
+    ///
+    /// Authentications are reused for policies with the same action (see
+    /// [`PolicyBuilder::action_id`]), even on targets other than Linux. A
+    /// failed authentication forgets the previous one for its action. Time
+    /// spent suspended counts towards the duration. Defaults to never reusing
+    /// authentications.
+    ///
+    /// An authentication is only reused if the policy it used didn't allow
+    /// any method this policy doesn't, so e.g. a password-only authentication
+    /// isn't reused for a policy that requires biometrics, or every method
+    /// (see [`PolicyBuilder::require_all`]).
+    #[inline]
+    #[must_use]
+    pub const fn reuse_duration(self, reuse_duration: Duration) -> Self {
+        Self {
+            reuse_duration: Some(reuse_duration),
+            ..self
+        }
+    }
+
+    /// Constructs the policy.
+    ///
+    /// Returns an error naming the unsupported combination if the specified
+    /// configuration is not valid for the current target.
+    #[inline]
+    pub const fn build(self) -> std::result::Result<Policy, PolicyError> {
+        match self.current_mapping() {
+            Ok(mapping) => Ok(self.build_unchecked(mapping)),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns how the policy is mapped onto `target`'s native authentication
+    /// API, or why it isn't valid for `target`.
+    ///
+    /// This doesn't depend on the target being compiled for, so a policy can
+    /// be checked against every target from any host.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use robius_authentication::{ApplePolicy, PolicyBuilder, PolicyError, PolicyMapping, Target};
+    ///
+    /// let policy = PolicyBuilder::new().biometrics(None).password(true);
+    ///
+    /// assert_eq!(
+    ///     policy.mapping(Target::Apple),
+    ///     Err(PolicyError::PasswordWithoutBiometrics)
+    /// );
+    /// assert_eq!(
+    ///     policy.mapping(Target::WatchOs),
+    ///     Ok(PolicyMapping::Apple(
+    ///         ApplePolicy::DeviceOwnerAuthenticationWithWristDetection
+    ///     ))
+    /// );
+    /// ```
+    #[inline]
+    pub const fn mapping(&self, target: Target) -> std::result::Result<PolicyMapping, PolicyError> {
+        target::map(self, target)
+    }
+
+    /// Constructs the policy, panicking if it isn't valid for the current
+    /// target.
+    ///
+    /// This is used by [`policy!`], as the result of [`build`](Self::build)
+    /// can't be unwrapped in `const` items.
+    #[doc(hidden)]
+    pub const fn __build_or_panic(self) -> Policy {
+        match self.current_mapping() {
+            Ok(mapping) => self.build_unchecked(mapping),
+            Err(error) => panic!("{}", error.message()),
+        }
+    }
+
+    const fn current_mapping(&self) -> std::result::Result<PolicyMapping, PolicyError> {
+        match Target::current() {
+            Some(target) => self.mapping(target),
+            None => Err(PolicyError::UnsupportedTarget),
+        }
+    }
+
+    const fn build_unchecked(self, mapping: PolicyMapping) -> Policy {
+        Policy {
+            inner: sys::Policy::new(mapping, self.prompt),
+            biometrics: self.biometrics,
+            password: self.password,
+            watch: self.watch,
+            require_all: self.require_all,
+            action_id: Cow::Borrowed(self.action_id),
+            timeout: self.timeout,
+            reuse_duration: self.reuse_duration,
+        }
     }
 }
 
 /// An authentication policy.
-#[derive(Debug)]
+///
+/// Policies are built using [`PolicyBuilder`], usually in `const` items.
+/// Strings that are only known at runtime can be set on the built policy
+/// instead.
+#[derive(Clone, Debug)]
 pub struct Policy {
     inner: sys::Policy,
+    biometrics: Option<BiometricStrength>,
+    password: bool,
+    watch: bool,
+    require_all: bool,
+    /// The action that authentications are reused for.
+    action_id: Cow<'static, str>,
+    timeout: Option<Duration>,
+    reuse_duration: Option<Duration>,
+}
+
+impl Policy {
+    /// Returns the biometric strength the policy allows, if it allows
+    /// biometrics.
+    #[inline]
+    pub const fn biometrics(&self) -> Option<BiometricStrength> {
+        self.biometrics
+    }
+
+    /// Returns whether the policy allows passwords.
+    #[inline]
+    pub const fn password(&self) -> bool {
+        self.password
+    }
+
+    /// Returns whether the user must authenticate using every method the
+    /// policy allows.
+    #[inline]
+    pub const fn require_all(&self) -> bool {
+        self.require_all
+    }
+
+    /// Returns the policy's polkit action.
+    #[inline]
+    pub fn action_id(&self) -> &str {
+        &self.action_id
+    }
+
+    /// Returns how long authentication may take, if it has a timeout.
+    #[inline]
+    pub const fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns how long authentications may be reused, if they can be.
+    #[inline]
+    pub const fn reuse_duration(&self) -> Option<Duration> {
+        self.reuse_duration
+    }
+
+    /// Returns the policy with its polkit action set to `action_id`.
+    ///
+    /// This is equivalent to [`PolicyBuilder::action_id`], but the action
+    /// doesn't need to be `'static`. Returns
+    /// [`PolicyError::InvalidActionId`] if the action isn't valid for the
+    /// current target.
+    ///
+    /// This only has an effect on Linux.
+    #[inline]
+    pub fn with_action_id(
+        self,
+        action_id: impl Into<Cow<'static, str>>,
+    ) -> std::result::Result<Self, PolicyError> {
+        let action_id = action_id.into();
+        Ok(Self {
+            inner: self.inner.with_action_id(action_id.clone())?,
+            action_id,
+            ..self
+        })
+    }
+
+    /// Returns the policy with its PAM service set to `service`.
+    ///
+    /// This is equivalent to [`PolicyBuilder::pam_service`], but the service
+    /// doesn't need to be `'static`. Returns
+    /// [`PolicyError::InvalidPamService`] if the service isn't valid for the
+    /// current target.
+    ///
+    /// This only has an effect on Linux.
+    #[inline]
+    pub fn with_pam_service(
+        self,
+        service: impl Into<Cow<'static, str>>,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(Self {
+            inner: self.inner.with_pam_service(service.into())?,
+            ..self
+        })
+    }
 }
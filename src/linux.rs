@@ -0,0 +1,10 @@
+//! The system services used to authenticate on Linux.
+//!
+//! [`SystemAuthenticator`](crate::SystemAuthenticator) combines these as
+//! described in the [crate documentation](crate#linux). Each of them can also
+//! be used on its own using
+//! [`Context::with_authenticator`](crate::Context::with_authenticator), e.g.
+//! to only accept fingerprints, or as part of a custom
+//! [`Authenticator`](crate::Authenticator).
+
+pub use crate::sys::linux::{Fprintd, Pam, Polkit, Tty};
@@ -0,0 +1,113 @@
+/// Constructs a [`Policy`](crate::Policy), failing to compile if it isn't
+/// valid for the current target.
+///
+/// Each field calls the [`PolicyBuilder`](crate::PolicyBuilder) method of the
+/// same name, so the arguments must be constant.
+///
+/// # Examples
+///
+/// ```
+/// use robius_authentication::{policy, BiometricStrength, Policy};
+///
+/// const POLICY: Policy = policy! {
+///     biometrics: Some(BiometricStrength::Strong),
+///     password: true,
+///     watch: true,
+/// };
+/// ```
+///
+/// A policy that allows neither biometrics nor passwords isn't valid on any
+/// target:
+///
+/// ```compile_fail
+/// use robius_authentication::{policy, Policy};
+///
+/// const POLICY: Policy = policy! {
+///     biometrics: None,
+///     password: false,
+/// };
+/// ```
+#[macro_export]
+macro_rules! policy {
+    ($($method:ident: $value:expr),* $(,)?) => {{
+        const POLICY: $crate::Policy = $crate::PolicyBuilder::new()
+            $(.$method($value))*
+            .__build_or_panic();
+        POLICY
+    }};
+}
+
+/// Constructs a [`Text`](crate::Text), failing to compile if the Windows text
+/// is too long.
+///
+/// The Windows title and description are given as a tuple, which is passed to
+/// [`WindowsText::new`](crate::WindowsText::new), so they must be constant.
+/// The fields must be given in the order shown.
+///
+/// # Examples
+///
+/// ```
+/// use robius_authentication::{text, AndroidText, LinuxText, Text};
+///
+/// const TEXT: Text = text! {
+///     android: AndroidText {
+///         title: "Title",
+///         subtitle: None,
+///         description: None,
+///     },
+///     apple: "authenticate",
+///     windows: ("Title", "Description"),
+///     linux: LinuxText {
+///         message: "Authentication is required to continue",
+///     },
+/// };
+/// ```
+///
+/// The Windows title can be at most 128 bytes long:
+///
+/// ```compile_fail
+/// use robius_authentication::{text, AndroidText, LinuxText, Text};
+///
+/// const TEXT: Text = text! {
+///     android: AndroidText {
+///         title: "Title",
+///         subtitle: None,
+///         description: None,
+///     },
+///     apple: "authenticate",
+///     windows: (
+///         concat!(
+///             "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+///             "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+///             "!",
+///         ),
+///         "Description",
+///     ),
+///     linux: LinuxText {
+///         message: "Authentication is required to continue",
+///     },
+/// };
+/// ```
+#[macro_export]
+macro_rules! text {
+    (
+        android: $android:expr,
+        apple: $apple:expr,
+        windows: ($title:expr, $description:expr $(,)?),
+        linux: $linux:expr $(,)?
+    ) => {
+        $crate::Text {
+            android: $android,
+            apple: $apple,
+            windows: {
+                const WINDOWS: $crate::WindowsText<'static, 'static> =
+                    match $crate::WindowsText::new($title, $description) {
+                        Some(text) => text,
+                        None => panic!("the Windows title or description is too long"),
+                    };
+                WINDOWS
+            },
+            linux: $linux,
+        }
+    };
+}
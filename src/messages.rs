@@ -0,0 +1,187 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::Error;
+
+/// A message explaining an [`Error`] to the user, and a hint on how to fix it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorMessage {
+    /// What went wrong, e.g. "No fingerprints or faces are enrolled."
+    pub message: Cow<'static, str>,
+    /// What the user can do about it, e.g. "Add one in your device's settings,
+    /// then try again.", if anything.
+    pub hint: Option<Cow<'static, str>>,
+}
+
+impl ErrorMessage {
+    /// Returns a message with the given text and hint.
+    #[inline]
+    pub fn new(
+        message: impl Into<Cow<'static, str>>,
+        hint: Option<impl Into<Cow<'static, str>>>,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            hint: hint.map(Into::into),
+        }
+    }
+}
+
+/// The user-facing messages for each [`Error`], by locale.
+///
+/// A catalog contains English messages for every error, and any number of
+/// locales added using [`add`](Self::add). Messages are identified by
+/// [`Error::message_id`], and a locale needn't translate all of them: any that
+/// are missing fall back to a less specific locale, e.g. `de-AT` to `de`, and
+/// finally to English.
+///
+/// Locales are BCP 47 language tags such as `pt-BR`. POSIX locale names such
+/// as `pt_BR.UTF-8` are also accepted, and tags are matched
+/// case-insensitively.
+///
+/// ```
+/// use robius_authentication::{Catalog, Error, ErrorMessage};
+///
+/// let catalog = Catalog::new().add(
+///     "de",
+///     [(
+///         "not-enrolled",
+///         ErrorMessage::new(
+///             "Es sind keine Fingerabdrücke oder Gesichter registriert.",
+///             Some("Füge einen in den Einstellungen hinzu und versuche es erneut."),
+///         ),
+///     )],
+/// );
+///
+/// let message = catalog.message(&Error::NotEnrolled, "de_AT.UTF-8");
+/// assert_eq!(message.message, "Es sind keine Fingerabdrücke oder Gesichter registriert.");
+///
+/// // Untranslated messages fall back to English.
+/// let message = catalog.message(&Error::Exhausted, "de");
+/// assert_eq!(message, Error::Exhausted.message());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Catalog {
+    locales: HashMap<String, HashMap<Cow<'static, str>, ErrorMessage>>,
+}
+
+impl Catalog {
+    /// Returns a catalog containing only the English messages.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds messages for `locale`, keyed by [`Error::message_id`], replacing
+    /// any it already has with the same IDs.
+    #[must_use]
+    pub fn add<I, K>(mut self, locale: &str, messages: I) -> Self
+    where
+        I: IntoIterator<Item = (K, ErrorMessage)>,
+        K: Into<Cow<'static, str>>,
+    {
+        self.locales.entry(normalize(locale)).or_default().extend(
+            messages
+                .into_iter()
+                .map(|(id, message)| (id.into(), message)),
+        );
+        self
+    }
+
+    /// Returns the message for `error` in `locale`, falling back to less
+    /// specific locales and then English.
+    pub fn message(&self, error: &Error, locale: &str) -> ErrorMessage {
+        let id = error.message_id();
+        let mut locale = normalize(locale);
+        loop {
+            if let Some(message) = self
+                .locales
+                .get(&locale)
+                .and_then(|messages| messages.get(id))
+            {
+                return message.clone();
+            }
+            match locale.rfind('-') {
+                Some(index) => locale.truncate(index),
+                None => return english(id),
+            }
+        }
+    }
+}
+
+/// Converts a language tag or POSIX locale name to a lowercase language tag,
+/// e.g. `pt_BR.UTF-8` to `pt-br`.
+fn normalize(locale: &str) -> String {
+    let end = locale.find(['.', '@']).unwrap_or(locale.len());
+    locale[..end].replace('_', "-").to_ascii_lowercase()
+}
+
+/// Returns the English message with the given ID.
+pub(crate) fn english(id: &str) -> ErrorMessage {
+    let (message, hint) = match id {
+        "authentication" => ("Your identity couldn't be verified.", Some("Try again.")),
+        "exhausted" => (
+            "There were too many failed attempts.",
+            Some("Wait a moment, or unlock your device using its passcode, then try again."),
+        ),
+        "unavailable" => (
+            "This sign-in method isn't available on this device.",
+            Some("Use another sign-in method."),
+        ),
+        "user-canceled" | "app-canceled" => ("Authentication was canceled.", None),
+        "timeout" => ("Authentication timed out.", Some("Try again.")),
+        "system-canceled" => ("Authentication was interrupted.", Some("Try again.")),
+        "biometry-disconnected" => (
+            "Your biometric accessory isn't connected.",
+            Some("Connect it, then try again."),
+        ),
+        "not-paired" => (
+            "No biometric accessory is paired with this device.",
+            Some("Pair one in your device's settings, then try again."),
+        ),
+        "not-enrolled" => (
+            "No fingerprints or faces are enrolled.",
+            Some("Add one in your device's settings, then try again."),
+        ),
+        "not-interactive" => (
+            "The sign-in prompt couldn't be shown.",
+            Some("Try again from a session where the prompt can be shown."),
+        ),
+        "watch-not-available" => (
+            "Your watch couldn't be used to sign in.",
+            Some("Make sure it's nearby and unlocked, then try again."),
+        ),
+        "invalid-dimensions" => (
+            "The sign-in prompt couldn't be shown.",
+            Some("Make the window larger, then try again."),
+        ),
+        "user-fallback" => ("Another sign-in method was chosen.", None),
+        "passcode-not-set" => (
+            "No passcode is set on this device.",
+            Some("Set one in your device's settings, then try again."),
+        ),
+        "update-required" => (
+            "A security update is required.",
+            Some("Update your device, then try again."),
+        ),
+        "busy" => (
+            "The biometric sensor is busy.",
+            Some("Wait a moment, then try again."),
+        ),
+        "disabled-by-policy" => (
+            "This sign-in method has been disabled by your organization.",
+            Some("Contact your administrator, or use another sign-in method."),
+        ),
+        "not-configured" => (
+            "No biometric sensor is set up for your account.",
+            Some("Set one up in your device's settings, then try again."),
+        ),
+        _ => (
+            "Something went wrong while signing in.",
+            Some("Try again. If the problem persists, restart your device."),
+        ),
+    };
+    ErrorMessage {
+        message: Cow::Borrowed(message),
+        hint: hint.map(Cow::Borrowed),
+    }
+}
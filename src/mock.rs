@@ -0,0 +1,395 @@
+//! A scripted backend for testing code that authenticates, without showing
+//! any prompts.
+//!
+//! A [`MockBackend`] returns queued [`Outcome`]s in order, and records the
+//! text and policy of each request. Contexts created using
+//! [`Context::mock`](crate::Context::mock) authenticate using it, and behave
+//! the same way with both `blocking_authenticate` and `authenticate`. An
+//! outcome's delay blocks the calling thread of `blocking_authenticate`,
+//! while `authenticate` returns a future that waits for it without blocking
+//! the executor. Dropping the future ends the request.
+//!
+//! ```
+//! use robius_authentication::{
+//!     mock::{MockBackend, Outcome},
+//!     policy, text, AndroidText, AuthenticationMethod, Context, Error, LinuxText, Policy,
+//!     Text,
+//! };
+//!
+//! const POLICY: Policy = policy! {};
+//! const TEXT: Text = text! {
+//!     android: AndroidText {
+//!         title: "Title",
+//!         subtitle: None,
+//!         description: None,
+//!     },
+//!     apple: "authenticate",
+//!     windows: ("Title", "Description"),
+//!     linux: LinuxText {
+//!         message: "Authentication is required to continue",
+//!     },
+//! };
+//!
+//! let backend = MockBackend::new();
+//! backend.push(Outcome::success(AuthenticationMethod::Biometric));
+//! backend.push(Outcome::error(Error::UserCanceled));
+//!
+//! let context = Context::mock(&backend);
+//! assert!(context.blocking_authenticate(TEXT, &POLICY).is_ok());
+//! assert!(matches!(
+//!     context.blocking_authenticate(TEXT, &POLICY),
+//!     Err(Error::UserCanceled)
+//! ));
+//!
+//! let requests = backend.requests();
+//! assert_eq!(requests.len(), 2);
+//! assert_eq!(requests[0].text.apple, "authenticate");
+//! ```
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::mpsc::{self, RecvTimeoutError},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+#[cfg(feature = "async")]
+use crate::authenticator::BoxFuture;
+use crate::{
+    AuthEvent, Authentication, AuthenticationMethod, AuthenticationRequest, Authenticator,
+    Availability, AvailabilityStatus, Error, Policy, Result, TextBuf,
+};
+
+/// A backend that returns scripted outcomes.
+///
+/// Clones share the same queue and recorded requests.
+#[derive(Clone, Debug, Default)]
+pub struct MockBackend {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<State>,
+    /// Notified when the request in flight is cancelled.
+    cancelled: Condvar,
+}
+
+#[derive(Debug)]
+struct State {
+    outcomes: VecDeque<Outcome>,
+    requests: Vec<MockRequest>,
+    availability: Availability,
+    in_flight: bool,
+    cancelled: bool,
+    /// Woken when the asynchronous request in flight is cancelled.
+    #[cfg(feature = "async")]
+    waker: Option<Waker>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            outcomes: VecDeque::new(),
+            requests: Vec::new(),
+            availability: Availability {
+                status: AvailabilityStatus::Available,
+                biometry: None,
+            },
+            in_flight: false,
+            cancelled: false,
+            #[cfg(feature = "async")]
+            waker: None,
+        }
+    }
+}
+
+impl MockBackend {
+    /// Returns a backend with no queued outcomes, which reports that
+    /// authentication is available.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the outcome of a future request.
+    pub fn push(&self, outcome: Outcome) {
+        self.shared
+            .state
+            .lock()
+            .unwrap()
+            .outcomes
+            .push_back(outcome);
+    }
+
+    /// Returns the number of queued outcomes that haven't been used yet.
+    pub fn pending(&self) -> usize {
+        self.shared.state.lock().unwrap().outcomes.len()
+    }
+
+    /// Returns the requests made so far, in order.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.shared.state.lock().unwrap().requests.clone()
+    }
+
+    /// Sets the availability reported by
+    /// [`Context::availability`](crate::Context::availability).
+    pub fn set_availability(&self, availability: Availability) {
+        self.shared.state.lock().unwrap().availability = availability;
+    }
+
+    /// Records `request`, reports the next queued outcome's events and
+    /// returns the outcome.
+    fn start(&self, request: &AuthenticationRequest<'_>) -> Outcome {
+        let outcome = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.requests.push(MockRequest {
+                text: TextBuf::from(request.text()),
+                policy: request.policy().clone(),
+            });
+            state.in_flight = true;
+            state.cancelled = false;
+            state.outcomes.pop_front()
+        };
+        let Some(mut outcome) = outcome else {
+            self.shared.state.lock().unwrap().in_flight = false;
+            panic!("no mock outcome is queued");
+        };
+
+        for event in outcome.events.drain(..) {
+            request.report(event);
+        }
+        outcome
+    }
+}
+
+impl Authenticator for MockBackend {
+    /// Authenticates using the next queued outcome.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no outcome is queued.
+    fn blocking_authenticate(&self, request: AuthenticationRequest<'_>) -> Result<Authentication> {
+        let outcome = self.start(&request);
+
+        let state = self.shared.state.lock().unwrap();
+        let (mut state, _) = self
+            .shared
+            .cancelled
+            .wait_timeout_while(state, outcome.delay, |state| !state.cancelled)
+            .unwrap();
+        state.in_flight = false;
+        if state.cancelled {
+            return Err(Error::AppCanceled);
+        }
+        outcome.result
+    }
+
+    /// Authenticates using the next queued outcome, without blocking while
+    /// its delay passes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no outcome is queued.
+    #[cfg(feature = "async")]
+    fn authenticate<'a>(
+        &'a self,
+        request: AuthenticationRequest<'a>,
+    ) -> BoxFuture<'a, Result<Authentication>> {
+        Box::pin(async move {
+            let outcome = self.start(&request);
+            Delay::new(self.shared.clone(), outcome).await
+        })
+    }
+
+    fn availability(&self, _: &Policy) -> Result<Availability> {
+        Ok(self.shared.state.lock().unwrap().availability)
+    }
+
+    fn cancel(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.in_flight {
+            state.cancelled = true;
+            self.shared.cancelled.notify_all();
+            #[cfg(feature = "async")]
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// A future that waits for an outcome's delay to pass, or for the request to
+/// be cancelled.
+#[cfg(feature = "async")]
+struct Delay {
+    shared: Arc<Shared>,
+    /// The timer of a non-zero delay.
+    timer: Option<Arc<Mutex<Timer>>>,
+    /// The outcome's result, until the future completes.
+    result: Option<Result<Authentication>>,
+    /// Wakes the timer's thread when dropped.
+    _stop: Option<mpsc::Sender<()>>,
+}
+
+#[cfg(feature = "async")]
+#[derive(Default)]
+struct Timer {
+    elapsed: bool,
+    /// Woken once the delay has passed.
+    waker: Option<Waker>,
+}
+
+#[cfg(feature = "async")]
+impl Delay {
+    fn new(shared: Arc<Shared>, outcome: Outcome) -> Self {
+        let (timer, stop) = if outcome.delay.is_zero() {
+            (None, None)
+        } else {
+            let (stop, stopped) = mpsc::channel::<()>();
+            let timer = Arc::new(Mutex::new(Timer::default()));
+
+            let thread_timer = timer.clone();
+            thread::spawn(move || {
+                if stopped.recv_timeout(outcome.delay) != Err(RecvTimeoutError::Timeout) {
+                    return;
+                }
+                let mut timer = thread_timer.lock().unwrap();
+                timer.elapsed = true;
+                if let Some(waker) = timer.waker.take() {
+                    waker.wake();
+                }
+            });
+            (Some(timer), Some(stop))
+        };
+
+        Self {
+            shared,
+            timer,
+            result: Some(outcome.result),
+            _stop: stop,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl Future for Delay {
+    type Output = Result<Authentication>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let mut state = this.shared.state.lock().unwrap();
+        if state.cancelled {
+            state.in_flight = false;
+            state.waker = None;
+            this.result = None;
+            return Poll::Ready(Err(Error::AppCanceled));
+        }
+
+        let elapsed = this.timer.as_ref().is_none_or(|timer| {
+            let mut timer = timer.lock().unwrap();
+            if !timer.elapsed {
+                timer.waker = Some(cx.waker().clone());
+            }
+            timer.elapsed
+        });
+        if elapsed {
+            state.in_flight = false;
+            state.waker = None;
+            return Poll::Ready(this.result.take().expect("delay polled after completion"));
+        }
+
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "async")]
+impl Drop for Delay {
+    fn drop(&mut self) {
+        // The request ends if the future is dropped before completing.
+        if self.result.is_some() {
+            let mut state = self.shared.state.lock().unwrap();
+            state.in_flight = false;
+            state.waker = None;
+        }
+    }
+}
+
+/// A request made to a [`MockBackend`].
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    /// The text the prompt would have shown.
+    pub text: TextBuf,
+    /// The policy that was requested.
+    pub policy: Policy,
+}
+
+/// The scripted outcome of a request.
+///
+/// Events are reported to the context's handler as soon as the request
+/// starts, followed by the result once the delay has passed. Cancelling the
+/// request during the delay fails it with [`Error::AppCanceled`] instead.
+#[derive(Debug)]
+pub struct Outcome {
+    result: Result<Authentication>,
+    events: Vec<AuthEvent>,
+    delay: Duration,
+}
+
+impl Outcome {
+    /// Returns an outcome in which the user authenticates using `method`.
+    pub fn success(method: AuthenticationMethod) -> Self {
+        Self::authenticated(Authentication::new(Some(method), None, None))
+    }
+
+    /// Returns an outcome in which the request succeeds with `authentication`.
+    pub fn authenticated(authentication: Authentication) -> Self {
+        Self {
+            result: Ok(authentication),
+            events: Vec::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Returns an outcome in which the request fails with `error`, e.g.
+    /// [`Error::UserCanceled`].
+    pub fn error(error: Error) -> Self {
+        Self {
+            result: Err(error),
+            events: Vec::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Returns an outcome in which the user fails `attempts` times, after
+    /// which the request fails with [`Error::Exhausted`].
+    ///
+    /// Each attempt is reported as [`AuthEvent::AttemptFailed`].
+    pub fn exhausted_after(attempts: usize) -> Self {
+        Self {
+            events: vec![AuthEvent::AttemptFailed; attempts],
+            ..Self::error(Error::Exhausted)
+        }
+    }
+
+    /// Reports `event` before the result.
+    #[must_use]
+    pub fn event(mut self, event: AuthEvent) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Delays the result by `delay`.
+    #[must_use]
+    pub fn delay(self, delay: Duration) -> Self {
+        Self { delay, ..self }
+    }
+}
@@ -0,0 +1,177 @@
+//! Generation of the packaging metadata required for authentication.
+//!
+//! Every target needs some metadata outside of the binary before
+//! authentication works:
+//! - Android requires the `USE_BIOMETRIC` permission in `AndroidManifest.xml`.
+//! - iOS requires `NSFaceIDUsageDescription` in `Info.plist` to use Face ID.
+//! - Linux requires a polkit policy file declaring every action used by a
+//!   [`Policy`](crate::Policy).
+//!
+//! A [`Declaration`] describes the app's actions once, and generates all of
+//! the above. It is pure Rust and can be used on any host, e.g. from a build
+//! script or an `xtask`.
+//!
+//! ```
+//! use robius_authentication::packaging::{Action, Authorization, Declaration, Defaults};
+//!
+//! const UNLOCK_VAULT: &str = "com.example.app.unlock-vault";
+//!
+//! let declaration = Declaration {
+//!     vendor: "Example",
+//!     vendor_url: Some("https://example.com"),
+//!     icon_name: None,
+//!     usage_description: "Unlock your vault with Face ID",
+//!     actions: &[Action {
+//!         id: UNLOCK_VAULT,
+//!         description: "Unlock the vault",
+//!         message: "Authentication is required to unlock the vault",
+//!         defaults: Defaults::all(Authorization::AuthSelfKeep),
+//!         annotations: &[],
+//!     }],
+//! };
+//!
+//! let policy = declaration.polkit_policy();
+//! robius_authentication::packaging::validate_polkit_policy(&policy, &[UNLOCK_VAULT])
+//!     .expect("invalid policy");
+//! ```
+
+mod polkit;
+mod xml;
+
+pub use polkit::{is_valid_action_id, validate_polkit_policy, ValidationError};
+
+/// A declaration of the actions an app authenticates.
+#[derive(Clone, Debug)]
+pub struct Declaration<'a> {
+    /// The name of the app's vendor.
+    ///
+    /// This is shown by some polkit authentication agents.
+    pub vendor: &'a str,
+    /// The URL of the app's vendor.
+    pub vendor_url: Option<&'a str>,
+    /// The name of the icon shown by polkit authentication agents.
+    pub icon_name: Option<&'a str>,
+    /// The reason the app uses Face ID.
+    ///
+    /// This is shown by iOS the first time the app uses Face ID.
+    pub usage_description: &'a str,
+    /// The actions the app authenticates.
+    pub actions: &'a [Action<'a>],
+}
+
+/// An action that requires authentication.
+///
+/// On Linux this corresponds to a polkit action, and should match the ID
+/// passed to [`PolicyBuilder::action_id`](crate::PolicyBuilder::action_id).
+#[derive(Clone, Debug)]
+pub struct Action<'a> {
+    /// The ID of the action, e.g. `com.example.app.unlock-vault`.
+    pub id: &'a str,
+    /// A short description of the action.
+    pub description: &'a str,
+    /// The message shown when authentication is required for the action.
+    pub message: &'a str,
+    /// The implicit authorizations for the action.
+    pub defaults: Defaults,
+    /// The action's annotations, as pairs of keys and values, e.g.
+    /// `("org.freedesktop.policykit.imply", "com.example.app.unlock-vault")`.
+    ///
+    /// See the [polkit documentation](https://www.freedesktop.org/software/polkit/docs/latest/polkit.8.html#polkit-declaring-actions)
+    /// for the annotations polkit understands.
+    pub annotations: &'a [(&'a str, &'a str)],
+}
+
+/// The implicit authorizations for an action, depending on the session the
+/// request originates from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Defaults {
+    /// The authorization for clients in any session, e.g. over SSH.
+    pub any: Authorization,
+    /// The authorization for clients in inactive local sessions.
+    pub inactive: Authorization,
+    /// The authorization for clients in the active local session.
+    pub active: Authorization,
+}
+
+impl Defaults {
+    /// Returns defaults that use `authorization` for every session.
+    #[inline]
+    pub const fn all(authorization: Authorization) -> Self {
+        Self {
+            any: authorization,
+            inactive: authorization,
+            active: authorization,
+        }
+    }
+}
+
+/// An implicit authorization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Authorization {
+    /// The action is never authorized.
+    No,
+    /// The action is always authorized, without authentication.
+    Yes,
+    /// The user must authenticate as themselves.
+    AuthSelf,
+    /// The user must authenticate as an administrator.
+    AuthAdmin,
+    /// Like [`AuthSelf`](Self::AuthSelf), but the authorization is kept for a
+    /// short period of time.
+    AuthSelfKeep,
+    /// Like [`AuthAdmin`](Self::AuthAdmin), but the authorization is kept for a
+    /// short period of time.
+    AuthAdminKeep,
+}
+
+impl Authorization {
+    /// Returns the name of the authorization used in polkit policy files.
+    #[inline]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::No => "no",
+            Self::Yes => "yes",
+            Self::AuthSelf => "auth_self",
+            Self::AuthAdmin => "auth_admin",
+            Self::AuthSelfKeep => "auth_self_keep",
+            Self::AuthAdminKeep => "auth_admin_keep",
+        }
+    }
+
+    /// Parses the name of an authorization used in polkit policy files.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "no" => Self::No,
+            "yes" => Self::Yes,
+            "auth_self" => Self::AuthSelf,
+            "auth_admin" => Self::AuthAdmin,
+            "auth_self_keep" => Self::AuthSelfKeep,
+            "auth_admin_keep" => Self::AuthAdminKeep,
+            _ => return None,
+        })
+    }
+}
+
+impl Declaration<'_> {
+    /// Returns the elements that must be added to `AndroidManifest.xml`.
+    pub fn android_manifest(&self) -> String {
+        "<uses-permission android:name=\"android.permission.USE_BIOMETRIC\" />\n".to_owned()
+    }
+
+    /// Returns the entries that must be added to the app's `Info.plist`.
+    pub fn info_plist(&self) -> String {
+        format!(
+            "<key>NSFaceIDUsageDescription</key>\n<string>{}</string>\n",
+            xml::escape(self.usage_description)
+        )
+    }
+
+    /// Returns the polkit policy file declaring the app's actions.
+    ///
+    /// The file should be installed in `/usr/share/polkit-1/actions`, and is
+    /// conventionally named after the app's reverse domain name, e.g.
+    /// `com.example.app.policy`.
+    pub fn polkit_policy(&self) -> String {
+        polkit::generate(self)
+    }
+}
@@ -0,0 +1,196 @@
+use std::{collections::HashSet, fmt};
+
+use super::{xml, Authorization, Declaration};
+
+/// Returns whether polkit would accept `action_id`.
+///
+/// polkit only allows ASCII letters, digits, periods and hyphens, e.g.
+/// `org.freedesktop.NetworkManager.network-control`, and the first character
+/// must be a letter.
+pub const fn is_valid_action_id(action_id: &str) -> bool {
+    let bytes = action_id.as_bytes();
+    if bytes.is_empty() || !bytes[0].is_ascii_alphabetic() {
+        return false;
+    }
+
+    let mut i = 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'.' | b'-' => {}
+            _ => return false,
+        }
+        i += 1;
+    }
+    true
+}
+
+pub(super) fn generate(declaration: &Declaration) -> String {
+    let mut policy = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE policyconfig PUBLIC\n \
+         \"-//freedesktop//DTD PolicyKit Policy Configuration 1.0//EN\"\n \
+         \"http://www.freedesktop.org/standards/PolicyKit/1/policyconfig.dtd\">\n\
+         <policyconfig>\n",
+    );
+
+    push_element(&mut policy, 2, "vendor", declaration.vendor);
+    if let Some(vendor_url) = declaration.vendor_url {
+        push_element(&mut policy, 2, "vendor_url", vendor_url);
+    }
+    if let Some(icon_name) = declaration.icon_name {
+        push_element(&mut policy, 2, "icon_name", icon_name);
+    }
+
+    for action in declaration.actions {
+        policy.push_str(&format!("  <action id=\"{}\">\n", xml::escape(action.id)));
+        push_element(&mut policy, 4, "description", action.description);
+        push_element(&mut policy, 4, "message", action.message);
+
+        policy.push_str("    <defaults>\n");
+        push_element(&mut policy, 6, "allow_any", action.defaults.any.as_str());
+        push_element(
+            &mut policy,
+            6,
+            "allow_inactive",
+            action.defaults.inactive.as_str(),
+        );
+        push_element(
+            &mut policy,
+            6,
+            "allow_active",
+            action.defaults.active.as_str(),
+        );
+        policy.push_str("    </defaults>\n");
+
+        for (key, value) in action.annotations {
+            policy.push_str(&format!(
+                "    <annotate key=\"{}\">{}</annotate>\n",
+                xml::escape(key),
+                xml::escape(value)
+            ));
+        }
+
+        policy.push_str("  </action>\n");
+    }
+
+    policy.push_str("</policyconfig>\n");
+    policy
+}
+
+fn push_element(policy: &mut String, indent: usize, name: &str, text: &str) {
+    policy.push_str(&format!(
+        "{:indent$}<{name}>{}</{name}>\n",
+        "",
+        xml::escape(text)
+    ));
+}
+
+/// Checks that `policy` is a valid polkit policy file declaring every action
+/// in `action_ids`.
+///
+/// `action_ids` should contain the IDs of the [`Policy`](crate::Policy) values
+/// used in code, so that packaging can't drift out of sync with them.
+pub fn validate_polkit_policy(policy: &str, action_ids: &[&str]) -> Result<(), ValidationError> {
+    let root = xml::parse(policy).map_err(ValidationError::Syntax)?;
+    if root.name != "policyconfig" {
+        return Err(ValidationError::NotPolicyConfig);
+    }
+
+    let mut declared = HashSet::new();
+    for action in root.children("action") {
+        let id = action.attribute("id").unwrap_or_default();
+        if !is_valid_action_id(id) {
+            return Err(ValidationError::InvalidActionId(id.to_owned()));
+        }
+        if !declared.insert(id) {
+            return Err(ValidationError::DuplicateAction(id.to_owned()));
+        }
+
+        for element in ["description", "message"] {
+            if action
+                .child(element)
+                .is_none_or(|e| e.text.trim().is_empty())
+            {
+                return Err(ValidationError::MissingElement {
+                    action: id.to_owned(),
+                    element,
+                });
+            }
+        }
+
+        if action
+            .children("annotate")
+            .any(|annotation| annotation.attribute("key").unwrap_or_default().is_empty())
+        {
+            return Err(ValidationError::MissingAnnotationKey(id.to_owned()));
+        }
+
+        // Actions without defaults are never implicitly authorized, which is
+        // valid albeit not very useful.
+        if let Some(defaults) = action.child("defaults") {
+            for element in ["allow_any", "allow_inactive", "allow_active"] {
+                if let Some(value) = defaults.child(element) {
+                    let value = value.text.trim();
+                    if Authorization::from_name(value).is_none() {
+                        return Err(ValidationError::InvalidAuthorization {
+                            action: id.to_owned(),
+                            value: value.to_owned(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    match action_ids.iter().find(|id| !declared.contains(**id)) {
+        Some(id) => Err(ValidationError::UndeclaredAction((*id).to_owned())),
+        None => Ok(()),
+    }
+}
+
+/// An error produced when validating a polkit policy file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The file isn't well-formed XML.
+    Syntax(String),
+    /// The root element isn't `<policyconfig>`.
+    NotPolicyConfig,
+    /// An action has a missing or invalid ID.
+    InvalidActionId(String),
+    /// An action is declared more than once.
+    DuplicateAction(String),
+    /// An action is missing a required element.
+    MissingElement {
+        action: String,
+        element: &'static str,
+    },
+    /// An action has an invalid implicit authorization.
+    InvalidAuthorization { action: String, value: String },
+    /// An action has an annotation without a key.
+    MissingAnnotationKey(String),
+    /// An action used in code isn't declared by the file.
+    UndeclaredAction(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(message) => write!(f, "malformed policy: {message}"),
+            Self::NotPolicyConfig => write!(f, "root element isn't `<policyconfig>`"),
+            Self::InvalidActionId(id) => write!(f, "invalid action ID `{id}`"),
+            Self::DuplicateAction(id) => write!(f, "action `{id}` is declared more than once"),
+            Self::MissingElement { action, element } => {
+                write!(f, "action `{action}` has no `<{element}>`")
+            }
+            Self::InvalidAuthorization { action, value } => {
+                write!(f, "action `{action}` has invalid authorization `{value}`")
+            }
+            Self::MissingAnnotationKey(id) => {
+                write!(f, "action `{id}` has an annotation without a key")
+            }
+            Self::UndeclaredAction(id) => write!(f, "action `{id}` isn't declared"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
@@ -0,0 +1,244 @@
+//! A minimal XML reader, sufficient for validating generated metadata.
+//!
+//! Processing instructions, comments and the document type declaration are
+//! skipped. Namespaces, CDATA sections and custom entities aren't supported.
+
+/// Escapes `text` so it can be used as element content or an attribute value.
+pub(super) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[derive(Debug)]
+pub(super) struct Element {
+    pub(super) name: String,
+    pub(super) attributes: Vec<(String, String)>,
+    pub(super) children: Vec<Element>,
+    pub(super) text: String,
+}
+
+impl Element {
+    pub(super) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(super) fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    pub(super) fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+}
+
+/// Parses `document`, returning its root element.
+///
+/// On failure, returns a description of the syntax error.
+pub(super) fn parse(document: &str) -> Result<Element, String> {
+    let mut parser = Parser {
+        input: document,
+        position: 0,
+    };
+
+    parser.skip_misc()?;
+    let root = parser.element()?;
+    parser.skip_misc()?;
+
+    if parser.position == document.len() {
+        Ok(root)
+    } else {
+        Err(parser.error("unexpected content after the root element"))
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn error(&self, message: &str) -> String {
+        let line = self.input[..self.position].matches('\n').count() + 1;
+        format!("line {line}: {message}")
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{token}`")))
+        }
+    }
+
+    fn skip_until(&mut self, token: &str) -> Result<(), String> {
+        match self.rest().find(token) {
+            Some(index) => {
+                self.position += index + token.len();
+                Ok(())
+            }
+            None => Err(self.error(&format!("expected `{token}`"))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skips whitespace, comments, processing instructions and document type
+    /// declarations.
+    fn skip_misc(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if rest.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if rest.starts_with("<!DOCTYPE") {
+                self.skip_until(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.position += len;
+        Ok(rest[..len].to_owned())
+    }
+
+    fn element(&mut self) -> Result<Element, String> {
+        self.expect("<")?;
+        let name = self.name()?;
+        let mut element = Element {
+            name,
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+        };
+
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.position += 2;
+                return Ok(element);
+            } else if self.rest().starts_with('>') {
+                self.position += 1;
+                break;
+            }
+
+            let key = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(self.error("expected a quoted attribute value")),
+            };
+            self.position += 1;
+            let len = self
+                .rest()
+                .find(quote)
+                .ok_or_else(|| self.error("unterminated attribute value"))?;
+            let value = self.unescape(&self.rest()[..len])?;
+            self.position += len + 1;
+
+            if element
+                .attributes
+                .iter()
+                .any(|(existing, _)| *existing == key)
+            {
+                return Err(self.error(&format!("duplicate attribute `{key}`")));
+            }
+            element.attributes.push((key, value));
+        }
+
+        loop {
+            let len = self.rest().find('<').unwrap_or(self.rest().len());
+            let text = self.unescape(&self.rest()[..len])?;
+            element.text.push_str(&text);
+            self.position += len;
+
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error(&format!("unclosed element `{}`", element.name)));
+            } else if rest.starts_with("</") {
+                self.position += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(self.error(&format!(
+                        "expected `</{}>`, found `</{name}>`",
+                        element.name
+                    )));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            } else if rest.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else {
+                element.children.push(self.element()?);
+            }
+        }
+    }
+
+    fn unescape(&self, text: &str) -> Result<String, String> {
+        let mut unescaped = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = rest.find('&') {
+            unescaped.push_str(&rest[..start]);
+            rest = &rest[start + 1..];
+            let end = rest
+                .find(';')
+                .ok_or_else(|| self.error("unterminated entity reference"))?;
+
+            let entity = &rest[..end];
+            let c = match entity {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => entity
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error(&format!("unknown entity `&{entity};`")))?,
+            };
+            unescaped.push(c);
+            rest = &rest[end + 1..];
+        }
+        unescaped.push_str(rest);
+
+        Ok(unescaped)
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt;
+
+use crate::Result;
+
+/// A prompt used to converse with the user while authenticating.
+///
+/// This only has an effect on Linux, where it asks for the user's password
+/// when polkit has no authentication agent, or when authenticating using PAM.
+/// See [`PolicyBuilder::prompt`] for more details.
+///
+/// [`PolicyBuilder::prompt`]: crate::PolicyBuilder::prompt
+pub trait Prompt: Sync {
+    /// Asks the user for a secret, e.g. their password, without displaying
+    /// their input.
+    ///
+    /// Returning [`Error::UserCanceled`](crate::Error::UserCanceled) cancels
+    /// authentication.
+    fn secret(&self, message: &str) -> Result<String>;
+
+    /// Asks the user for text, e.g. a one-time code, displaying their input.
+    ///
+    /// Returning [`Error::UserCanceled`](crate::Error::UserCanceled) cancels
+    /// authentication.
+    fn text(&self, message: &str) -> Result<String>;
+
+    /// Shows an informational message, e.g. the reason for authenticating.
+    fn info(&self, message: &str);
+
+    /// Shows an error message, e.g. that the password was incorrect.
+    fn error(&self, message: &str);
+}
+
+impl fmt::Debug for dyn Prompt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Prompt")
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{Authenticator, Error, RawContext, Result, SystemAuthenticator};
+
+/// The environment variable that names the backend a [`Registry`] selects,
+/// e.g. `ROBIUS_AUTH_BACKEND=tty`.
+///
+/// Whoever controls the process's environment can use this to select a weaker
+/// backend, e.g. `pam` or `tty` instead of `polkit`, which don't check the
+/// policy's polkit action (see [`Registry`]).
+pub const BACKEND_VAR: &str = "ROBIUS_AUTH_BACKEND";
+
+/// A set of named backends, from which a [`Context`](crate::Context) selects
+/// the one it authenticates using.
+///
+/// Selecting a backend is opt-in: [`Context::new`](crate::Context::new) always
+/// uses the system's backend, while
+/// [`Context::from_registry`](crate::Context::from_registry) selects one when
+/// the context is created:
+/// 1. If the [`BACKEND_VAR`] environment variable is set, the backend it names
+///    is used.
+/// 2. Otherwise, if the registry's config file exists (see
+///    [`config_file`](Self::config_file)), the backend it names is used. Blank
+///    lines and comments starting with `#` are ignored.
+/// 3. Otherwise, the backends are probed in order of priority, highest first,
+///    and the first one that can be used in the current session (see
+///    [`Authenticator::probe`]) is used.
+///
+/// Backends named by the environment or config file aren't probed, so that
+/// they can be forced, but names that aren't registered are ignored. Which
+/// backend was selected, and why, is reported by
+/// [`Context::selection`](crate::Context::selection).
+///
+/// Note that overrides can downgrade authentication. On Linux, the policy's
+/// polkit action (see
+/// [`PolicyBuilder::action_id`](crate::PolicyBuilder::action_id)) is only
+/// checked by the `polkit` backend, and by the `system` backend when it
+/// verifies the password using polkit, while `fprintd`, `pam` and `tty` only
+/// verify the user. Applications that rely on the action's rules, e.g. to
+/// require an administrator, should register only the backends they trust in
+/// a [`Registry::new`], or check
+/// [`Context::selection`](crate::Context::selection) before authenticating.
+///
+/// [`Registry::system`] contains the system's backend, named `system`, which
+/// has the highest priority and can always be used. On Linux, it also contains
+/// each of the services it uses (see [`linux`](crate::linux)):
+///
+/// | Name      | Priority | Usable if                                          |
+/// |-----------|----------|----------------------------------------------------|
+/// | `system`  | 100      | Always                                             |
+/// | `polkit`  | 40       | polkit is running                                  |
+/// | `fprintd` | 30       | The user has enrolled a fingerprint                |
+/// | `pam`     | 20       | libpam is installed                                |
+/// | `tty`     | 10       | libpam is installed and the process has a terminal |
+///
+/// ```no_run
+/// use robius_authentication::{Context, Registry};
+///
+/// let registry = Registry::system(()).set_priority("system", 0);
+/// let context = Context::from_registry(registry).unwrap();
+///
+/// let selection = context.selection().unwrap();
+/// println!("authenticating using {}: {:?}", selection.name, selection.reason);
+/// ```
+#[derive(Debug)]
+pub struct Registry {
+    backends: Vec<Registration>,
+    config_file: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+struct Registration {
+    name: Cow<'static, str>,
+    priority: i32,
+    authenticator: Arc<dyn Authenticator>,
+}
+
+impl Default for Registry {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Registry {
+    /// Returns a registry without any backends, which reads the default config
+    /// file.
+    ///
+    /// The default config file is `robius-authentication/backend` in the
+    /// user's configuration directory, i.e. `$XDG_CONFIG_HOME`, or
+    /// `$HOME/.config` if it isn't set.
+    pub fn new() -> Self {
+        Self {
+            backends: Vec::new(),
+            config_file: default_config_file(),
+        }
+    }
+
+    /// Returns a registry containing the system's backends.
+    pub fn system(raw: RawContext) -> Self {
+        let registry = Self::new().register("system", 100, SystemAuthenticator::new(raw));
+        #[cfg(target_os = "linux")]
+        let registry = registry
+            .register("polkit", 40, crate::linux::Polkit::new())
+            .register("fprintd", 30, crate::linux::Fprintd::new())
+            .register("pam", 20, crate::linux::Pam::new())
+            .register("tty", 10, crate::linux::Tty::new());
+        registry
+    }
+
+    /// Adds a backend, replacing any backend with the same name.
+    ///
+    /// Backends with the same priority are probed in the order they were
+    /// registered.
+    #[must_use]
+    pub fn register(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        priority: i32,
+        authenticator: impl Authenticator + 'static,
+    ) -> Self {
+        let name = name.into();
+        self.backends.retain(|backend| backend.name != name);
+        self.backends.push(Registration {
+            name,
+            priority,
+            authenticator: Arc::new(authenticator),
+        });
+        self
+    }
+
+    /// Changes the priority of the backend named `name`, if it's registered.
+    #[must_use]
+    pub fn set_priority(mut self, name: &str, priority: i32) -> Self {
+        if let Some(backend) = self
+            .backends
+            .iter_mut()
+            .find(|backend| backend.name == name)
+        {
+            backend.priority = priority;
+        }
+        self
+    }
+
+    /// Sets the config file that names the backend to use, or disables it if
+    /// `path` is `None`.
+    #[must_use]
+    pub fn config_file(self, path: Option<PathBuf>) -> Self {
+        Self {
+            config_file: path,
+            ..self
+        }
+    }
+
+    /// Returns the names of the registered backends, in the order they're
+    /// probed.
+    pub fn names(&self) -> Vec<&str> {
+        self.by_priority()
+            .into_iter()
+            .map(|backend| &*backend.name)
+            .collect()
+    }
+
+    /// Selects a backend as described [above](Self).
+    ///
+    /// Fails with [`Error::Unavailable`] if none of the backends can be used.
+    pub(crate) fn select(self) -> Result<(Arc<dyn Authenticator>, Selection)> {
+        let mut unknown = None;
+        if let Some((name, reason)) = self.requested() {
+            match self.backends.iter().find(|backend| backend.name == name) {
+                Some(backend) => {
+                    let selection = Selection {
+                        name: backend.name.clone(),
+                        reason,
+                    };
+                    return Ok((backend.authenticator.clone(), selection));
+                }
+                None => unknown = Some(name),
+            }
+        }
+
+        let mut skipped = Vec::new();
+        for backend in self.by_priority() {
+            match backend.authenticator.probe() {
+                Ok(()) => {
+                    let selection = Selection {
+                        name: backend.name.clone(),
+                        reason: SelectionReason::Priority { skipped, unknown },
+                    };
+                    return Ok((backend.authenticator.clone(), selection));
+                }
+                Err(error) => skipped.push((backend.name.clone(), error)),
+            }
+        }
+        Err(Error::Unavailable)
+    }
+
+    /// Returns the backend named by the environment or config file, if any.
+    fn requested(&self) -> Option<(String, SelectionReason)> {
+        if let Some(name) = env::var(BACKEND_VAR).ok().filter(|name| !name.is_empty()) {
+            return Some((name, SelectionReason::Environment));
+        }
+        let path = self.config_file.as_ref()?;
+        let name = read_config(path)?;
+        Some((name, SelectionReason::ConfigFile(path.clone())))
+    }
+
+    fn by_priority(&self) -> Vec<&Registration> {
+        let mut backends: Vec<_> = self.backends.iter().collect();
+        // The sort is stable, so registration order breaks ties.
+        backends.sort_by_key(|backend| Reverse(backend.priority));
+        backends
+    }
+}
+
+/// The backend a [`Registry`] selected.
+#[derive(Debug)]
+pub struct Selection {
+    /// The name of the backend.
+    pub name: Cow<'static, str>,
+    /// Why the backend was selected.
+    pub reason: SelectionReason,
+}
+
+/// Why a [`Registry`] selected a backend.
+#[derive(Debug)]
+pub enum SelectionReason {
+    /// The backend was named by the [`BACKEND_VAR`] environment variable.
+    Environment,
+    /// The backend was named by the config file at the given path.
+    ConfigFile(PathBuf),
+    /// The backend has the highest priority of those that can be used.
+    Priority {
+        /// The backends with a higher priority that can't be used, and the
+        /// errors their probes failed with.
+        skipped: Vec<(Cow<'static, str>, Error)>,
+        /// The name given by the environment or config file, if it didn't
+        /// name a registered backend.
+        unknown: Option<String>,
+    },
+}
+
+/// Returns the path of the config file read by default.
+fn default_config_file() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .filter(|path| Path::new(path).is_absolute())
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".config")))?;
+    Some(config.join("robius-authentication").join("backend"))
+}
+
+/// Returns the backend named by the config file at `path`, ignoring blank lines
+/// and comments starting with `#`.
+fn read_config(path: &Path) -> Option<String> {
+    let config = fs::read_to_string(path).ok()?;
+    config
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+}
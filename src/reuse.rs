@@ -0,0 +1,111 @@
+use std::{borrow::Cow, collections::HashMap, sync::Mutex, time::Duration};
+
+use crate::{sys, Authentication, BiometricStrength, Policy, Result};
+
+/// A context's most recent successful authentication for each action, which
+/// policies with a reuse duration return instead of prompting again, if it
+/// verified at least what they require.
+///
+/// Times are measured using the system's uptime, which keeps advancing while
+/// the system is suspended, so a suspended device doesn't extend the window.
+#[derive(Debug, Default)]
+pub(crate) struct ReuseCache {
+    entries: Mutex<HashMap<Cow<'static, str>, Entry>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    /// The system's uptime when the user authenticated.
+    time: Duration,
+    requirements: Requirements,
+    authentication: Authentication,
+}
+
+/// The methods allowed by the policy an authentication used, which is all that
+/// is known about how the user authenticated on some targets.
+#[derive(Debug)]
+struct Requirements {
+    biometrics: Option<BiometricStrength>,
+    password: bool,
+    watch: bool,
+    require_all: bool,
+}
+
+impl Requirements {
+    fn new(policy: &Policy) -> Self {
+        Self {
+            biometrics: policy.biometrics,
+            password: policy.password,
+            watch: policy.watch,
+            require_all: policy.require_all,
+        }
+    }
+
+    /// Returns whether authenticating under these requirements verified at
+    /// least what `policy` requires.
+    fn satisfy(&self, policy: &Policy) -> bool {
+        let biometrics = match (policy.biometrics, self.biometrics) {
+            (Some(BiometricStrength::Weak), Some(_)) => true,
+            (Some(BiometricStrength::Strong), Some(strength)) => {
+                strength == BiometricStrength::Strong
+            }
+            (None, Some(_)) => false,
+            (_, None) => true,
+        };
+        let password = policy.password || !self.password;
+        let watch = policy.watch || !self.watch;
+
+        if policy.require_all {
+            // Every method must have been verified.
+            self.require_all && self.biometrics.is_some() && biometrics && self.password
+        } else if self.require_all {
+            // Any of the verified methods is enough.
+            (self.biometrics.is_some() && biometrics) || (self.password && policy.password)
+        } else {
+            // Any of the allowed methods may have been used.
+            biometrics && password && watch
+        }
+    }
+}
+
+impl ReuseCache {
+    /// Returns the authentication that can be reused for `policy`, if any.
+    ///
+    /// An authentication using a policy that allowed weaker methods, e.g. only
+    /// a password for a policy that requires every method, isn't reused.
+    pub(crate) fn get(&self, policy: &Policy) -> Option<Authentication> {
+        let reuse_duration = policy.reuse_duration?;
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(&policy.action_id)?;
+        let elapsed = sys::uptime().saturating_sub(entry.time);
+        (elapsed <= reuse_duration && entry.requirements.satisfy(policy))
+            .then(|| entry.authentication.clone())
+    }
+
+    /// Records the result of authenticating using `policy`, forgetting the
+    /// previous authentication for its action if it failed.
+    pub(crate) fn update(&self, policy: &Policy, result: &Result<Authentication>) {
+        let mut entries = self.entries.lock().unwrap();
+        match result {
+            Ok(authentication) if policy.reuse_duration.is_some() => {
+                entries.insert(
+                    policy.action_id.clone(),
+                    Entry {
+                        time: sys::uptime(),
+                        requirements: Requirements::new(policy),
+                        authentication: authentication.clone(),
+                    },
+                );
+            }
+            Ok(_) => {}
+            Err(_) => {
+                entries.remove(&policy.action_id);
+            }
+        }
+    }
+
+    /// Forgets every authentication.
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
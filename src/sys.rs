@@ -1,3 +1,8 @@
+#[cfg(any(target_vendor = "apple", target_os = "android", target_os = "linux"))]
+mod unix;
+#[cfg(any(target_vendor = "apple", target_os = "android", target_os = "linux"))]
+pub(crate) use unix::uptime;
+
 cfg_if::cfg_if! {
     if #[cfg(target_os = "android")] {
         mod android;
@@ -6,7 +11,7 @@ cfg_if::cfg_if! {
         mod apple;
         pub(crate) use apple::*;
     } else if #[cfg(target_os = "linux")] {
-        mod linux;
+        pub(crate) mod linux;
         pub(crate) use linux::*;
     } else if #[cfg(target_os = "windows")] {
         mod windows;
@@ -1,59 +1,113 @@
 mod callback;
 
-use callback::{Receiver, Sender};
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
+
+use callback::{Callback, Receiver};
 use jni::{
     objects::{GlobalRef, JObject, JValueGen},
     JNIEnv,
 };
 
-use crate::{BiometricStrength, Error, Result, Text};
+use crate::{
+    event::EventHandler, AndroidAuthenticators, Authentication, AuthenticationMethod, Availability,
+    BiometricStrength, BiometryKind, Error, PolicyError, PolicyMapping, Prompt, Result, Text,
+};
 
 pub(crate) type RawContext = ();
 
 // Actual contextual info is handled by the `robius-android-env` crate, so we
-// don't have to store any state here.
+// only store the request in flight, so that it can be cancelled.
 #[derive(Debug)]
-pub(crate) struct Context;
+pub(crate) struct Context {
+    request: Arc<Mutex<Option<Request>>>,
+}
 
 impl Context {
     pub(crate) fn new(_: RawContext) -> Self {
-        Self
+        Self {
+            request: Arc::default(),
+        }
+    }
+
+    pub(crate) fn handle(&self) -> Handle {
+        Handle {
+            request: self.request.clone(),
+        }
     }
 
     #[cfg(feature = "async")]
     pub(crate) async fn authenticate(
         &self,
-        text: Text<'_, '_, '_, '_, '_, '_>,
+        text: Text<'_, '_, '_, '_, '_, '_, '_>,
         policy: &Policy,
-    ) -> Result<()> {
-        if let Ok(inner) = self.authenticate_inner(text, policy)?.await {
-            inner
-        } else {
-            Err(Error::Unknown)
-        }
+        events: &EventHandler,
+    ) -> Result<Authentication> {
+        let (rx, signal) = self.authenticate_inner(text, policy, events)?;
+        let result = rx.await.unwrap_or(Err(Error::Unknown));
+        self.finish(&signal, policy, result)
     }
 
-    pub(crate) fn blocking_authenticate(&self, text: Text, policy: &Policy) -> Result<()> {
+    pub(crate) fn blocking_authenticate(
+        &self,
+        text: Text,
+        policy: &Policy,
+        events: &EventHandler,
+    ) -> Result<Authentication> {
+        let (rx, signal) = self.authenticate_inner(text, policy, events)?;
         #[cfg(feature = "async")]
-        let result = self.authenticate_inner(text, policy)?.blocking_recv();
+        let result = rx.blocking_recv();
         #[cfg(not(feature = "async"))]
-        let result = self.authenticate_inner(text, policy)?.recv();
+        let result = rx.recv();
 
-        if let Ok(inner) = result {
-            inner
-        } else {
-            Err(Error::Unknown)
+        self.finish(&signal, policy, result.unwrap_or(Err(Error::Unknown)))
+    }
+
+    /// Ends the request using `signal`, returning its result.
+    fn finish(
+        &self,
+        signal: &GlobalRef,
+        policy: &Policy,
+        result: Result<Option<AuthenticationMethod>>,
+    ) -> Result<Authentication> {
+        let mut request = self.request.lock().unwrap();
+        // Another request may have started since.
+        let is_current = request
+            .as_ref()
+            .is_some_and(|request| request.signal.as_obj().as_raw() == signal.as_obj().as_raw());
+        let cancelled = is_current && request.take().is_some_and(|request| request.cancelled);
+
+        match result {
+            // The prompt reports being cancelled by the app in the same way as
+            // being cancelled by the system.
+            Err(Error::SystemCanceled) if cancelled => Err(Error::AppCanceled),
+            Err(e) => Err(e),
+            Ok(method) => {
+                // The prompt only accepts biometrics of the policy's class.
+                let strength = match method {
+                    Some(AuthenticationMethod::Biometric) => Some(policy.strength()),
+                    _ => None,
+                };
+                Ok(Authentication::new(method, strength, None))
+            }
         }
     }
 
-    fn authenticate_inner(&self, text: Text, policy: &Policy) -> Result<Receiver> {
+    fn authenticate_inner(
+        &self,
+        text: Text,
+        policy: &Policy,
+        events: &EventHandler,
+    ) -> Result<(Receiver, GlobalRef)> {
         robius_android_env::with_activity(|env, context| {
             let (tx, rx) = callback::channel();
 
             let callback_class = callback::get_callback_class(env)?;
 
             let callback_instance =
-                construct_callback(env, callback_class, Box::into_raw(Box::new(tx)))?;
+                construct_callback(env, callback_class, Callback::into_raw(tx, events.clone()))?;
             let cancellation_signal = construct_cancellation_signal(env)?;
             let executor = get_executor(env, context)?;
 
@@ -71,66 +125,164 @@ impl Context {
                 ],
             )?;
 
-            Ok(rx)
+            let signal = env.new_global_ref(cancellation_signal)?;
+            *self.request.lock().unwrap() = Some(Request {
+                signal: signal.clone(),
+                cancelled: false,
+            });
+            Ok((rx, signal))
+        })
+        .ok_or(Error::Unknown)?
+    }
+
+    pub(crate) fn availability(&self, policy: &Policy) -> Result<Availability> {
+        const BIOMETRIC_SUCCESS: i32 = 0;
+        const BIOMETRIC_ERROR_HW_UNAVAILABLE: i32 = 1;
+        const BIOMETRIC_ERROR_NONE_ENROLLED: i32 = 11;
+        const BIOMETRIC_ERROR_NO_HARDWARE: i32 = 12;
+        const BIOMETRIC_ERROR_SECURITY_UPDATE_REQUIRED: i32 = 15;
+        const BIOMETRIC_ERROR_UNSUPPORTED: i32 = -2;
+
+        robius_android_env::with_activity(|env, context| {
+            let class = env.find_class("android/hardware/biometrics/BiometricManager")?;
+            let manager = env
+                .call_method(
+                    context,
+                    "getSystemService",
+                    "(Ljava/lang/Class;)Ljava/lang/Object;",
+                    &[JValueGen::Object(&class)],
+                )?
+                .l()?;
+            let status = env
+                .call_method(
+                    manager,
+                    "canAuthenticate",
+                    "(I)I",
+                    &[JValueGen::Int(policy.authenticators.bits())],
+                )?
+                .i()?;
+
+            let result = match status {
+                BIOMETRIC_SUCCESS => Ok(()),
+                BIOMETRIC_ERROR_NONE_ENROLLED => Err(Error::NotEnrolled),
+                BIOMETRIC_ERROR_HW_UNAVAILABLE
+                | BIOMETRIC_ERROR_NO_HARDWARE
+                | BIOMETRIC_ERROR_UNSUPPORTED => Err(Error::Unavailable),
+                BIOMETRIC_ERROR_SECURITY_UPDATE_REQUIRED => Err(Error::UpdateRequired),
+                _ => Err(Error::Unknown),
+            };
+            Availability::from_result(result, biometry(env, context)?)
         })
         .ok_or(Error::Unknown)?
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct Policy {
-    #[allow(dead_code)]
-    strength: BiometricStrength,
-    password: bool,
+/// Returns the kind of biometry supported by the device, or the first one
+/// found if it supports several.
+fn biometry(env: &mut JNIEnv<'_>, context: &JObject<'_>) -> Result<Option<BiometryKind>> {
+    let package_manager = env
+        .call_method(
+            context,
+            "getPackageManager",
+            "()Landroid/content/pm/PackageManager;",
+            &[],
+        )?
+        .l()?;
+
+    for (feature, kind) in [
+        ("android.hardware.fingerprint", BiometryKind::Fingerprint),
+        ("android.hardware.biometrics.face", BiometryKind::Face),
+        ("android.hardware.biometrics.iris", BiometryKind::Iris),
+    ] {
+        let feature = env.new_string(feature)?;
+        let supported = env
+            .call_method(
+                &package_manager,
+                "hasSystemFeature",
+                "(Ljava/lang/String;)Z",
+                &[JValueGen::Object(&feature)],
+            )?
+            .z()?;
+        if supported {
+            return Ok(Some(kind));
+        }
+    }
+    Ok(None)
 }
 
 #[derive(Debug)]
-pub(crate) struct PolicyBuilder {
-    biometrics: Option<BiometricStrength>,
-    password: bool,
+struct Request {
+    /// The `CancellationSignal` passed to the prompt.
+    signal: GlobalRef,
+    cancelled: bool,
 }
 
-impl PolicyBuilder {
-    pub(crate) const fn new() -> Self {
-        Self {
-            biometrics: Some(BiometricStrength::Strong),
-            password: true,
-        }
-    }
+#[derive(Clone, Debug)]
+pub(crate) struct Handle {
+    request: Arc<Mutex<Option<Request>>>,
+}
+
+impl Handle {
+    pub(crate) fn cancel(&self) {
+        let mut request = self.request.lock().unwrap();
+        let Some(request) = request.as_mut() else {
+            return;
+        };
+        request.cancelled = true;
 
-    pub(crate) const fn biometrics(self, biometrics: Option<BiometricStrength>) -> Self {
-        Self { biometrics, ..self }
+        let signal = request.signal.clone();
+        let _ = robius_android_env::with_activity(|env, _| {
+            env.call_method(signal, "cancel", "()V", &[])
+        });
     }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Policy {
+    authenticators: AndroidAuthenticators,
+}
 
-    pub(crate) const fn password(self, password: bool) -> Self {
-        Self { password, ..self }
+impl Policy {
+    pub(crate) const fn new(mapping: PolicyMapping, _: Option<&'static dyn Prompt>) -> Self {
+        let PolicyMapping::Android(authenticators) = mapping else {
+            unreachable!()
+        };
+        Self { authenticators }
     }
 
-    pub(crate) const fn watch(self, _: bool) -> Self {
-        self
+    /// Returns the weakest biometric strength the policy allows.
+    fn strength(&self) -> BiometricStrength {
+        if self
+            .authenticators
+            .contains(AndroidAuthenticators::BIOMETRIC_WEAK)
+        {
+            BiometricStrength::Weak
+        } else {
+            BiometricStrength::Strong
+        }
     }
 
-    pub(crate) const fn wrist_detection(self, _: bool) -> Self {
-        self
+    pub(crate) fn with_action_id(
+        self,
+        _: Cow<'static, str>,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(self)
     }
 
-    pub(crate) const fn build(self) -> Option<Policy> {
-        if let Some(strength) = self.biometrics {
-            return Some(Policy {
-                strength,
-                password: self.password,
-            });
-        }
-        None
+    pub(crate) fn with_pam_service(
+        self,
+        _: Cow<'static, str>,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(self)
     }
 }
 
 fn construct_callback<'a>(
     env: &mut JNIEnv<'a>,
     class: &GlobalRef,
-    channel_ptr: *mut Sender,
+    callback_ptr: *mut Callback,
 ) -> Result<JObject<'a>> {
-    env.new_object(class, "(J)V", &[JValueGen::Long(channel_ptr as i64)])
+    env.new_object(class, "(J)V", &[JValueGen::Long(callback_ptr as i64)])
         .map_err(|e| e.into())
 }
 
@@ -192,20 +344,11 @@ fn construct_biometric_prompt<'a>(
             &[JValueGen::Object(&env.new_string(description)?.into())],
         )?;
     }
-    const STRONG: i32 = 0xf;
-    const WEAK: i32 = 0xff;
-    const CREDENTIAL: i32 = 0x8000;
-
     env.call_method(
         &builder,
         "setAllowedAuthenticators",
         "(I)Landroid/hardware/biometrics/BiometricPrompt$Builder;",
-        &[JValueGen::Int(
-            match policy.strength {
-                BiometricStrength::Strong => STRONG,
-                BiometricStrength::Weak => WEAK,
-            } | if policy.password { CREDENTIAL } else { 0 },
-        )],
+        &[JValueGen::Int(policy.authenticators.bits())],
     )?;
 
     env.call_method(
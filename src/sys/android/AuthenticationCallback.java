@@ -8,7 +8,10 @@ public class AuthenticationCallback extends BiometricPrompt.AuthenticationCallba
   private long pointer;
 
   /* TODO: There are neater ways of doing this */
-  private native void rustCallback(long pointer, int errorCode, int helpCode);
+  private native void rustCallback(long pointer, int errorCode, int authenticationType);
+
+  /* Reports progress while the prompt remains displayed. A null help string means an attempt failed. */
+  private native void rustEvent(long pointer, String helpString);
 
   public AuthenticationCallback(long pointer) {
     this.pointer = pointer;
@@ -19,15 +22,17 @@ public class AuthenticationCallback extends BiometricPrompt.AuthenticationCallba
   }
 
   /* This is called when the user presents an incorrect authenticator (e.g. fingerprint or password). However, the
-   * prompt remains displayed and so we don't really care, until the prompt dissapears, in which case
-   * `onAuthenticationError`, `onAuthenticationHelp`, or `onAuthenticationSucceeded` is called. */
-  public void onAuthenticationFailed() {}
+   * prompt remains displayed, until the prompt dissapears, in which case `onAuthenticationError` or
+   * `onAuthenticationSucceeded` is called. */
+  public void onAuthenticationFailed() {
+    rustEvent(pointer, null);
+  }
 
   public void onAuthenticationHelp(int helpCode, CharSequence helpString) {
-    rustCallback(pointer, 0, helpCode);
+    rustEvent(pointer, helpString.toString());
   }
 
   public void onAuthenticationSucceeded(BiometricPrompt.AuthenticationResult result) {
-    rustCallback(pointer, 0, 0);
+    rustCallback(pointer, 0, result.getAuthenticationType());
   }
 }
\ No newline at end of file
@@ -3,19 +3,20 @@ use std::sync::mpsc as channel_impl;
 use std::sync::OnceLock;
 
 use jni::{
-    objects::{GlobalRef, JClass, JObject, JValueGen},
+    objects::{GlobalRef, JClass, JObject, JString, JValueGen},
     sys::{jint, jlong},
     JNIEnv, NativeMethod,
 };
 #[cfg(feature = "async")]
 use tokio::sync::oneshot as channel_impl;
 
-use crate::{Error, Result};
+use crate::{event::EventHandler, AuthEvent, AuthenticationMethod, Error, PlatformError, Result};
 
 const AUTHENTICATION_CALLBACK_BYTECODE: &[u8] =
     include_bytes!(concat!(env!("OUT_DIR"), "/classes.dex"));
 
-type ChannelData = Result<()>;
+/// How the user authenticated, if they did.
+type ChannelData = Result<Option<AuthenticationMethod>>;
 
 pub(super) type Receiver = channel_impl::Receiver<ChannelData>;
 pub(super) type Sender = channel_impl::Sender<ChannelData>;
@@ -24,19 +25,36 @@ pub(super) fn channel() -> (Sender, Receiver) {
     channel_impl::channel()
 }
 
+/// The state shared with an `AuthenticationCallback`, which owns it until the
+/// prompt reports its result.
+pub(super) struct Callback {
+    sender: Sender,
+    events: EventHandler,
+}
+
+impl Callback {
+    pub(super) fn into_raw(sender: Sender, events: EventHandler) -> *mut Self {
+        Box::into_raw(Box::new(Self { sender, events }))
+    }
+}
+
 // NOTE: This must be kept in sync with the signature of `rust_callback`.
 const RUST_CALLBACK_SIGNATURE: &str = "(JII)V";
 
+// NOTE: This must be kept in sync with the signature of `rust_event`.
+const RUST_EVENT_SIGNATURE: &str = "(JLjava/lang/String;)V";
+
 // NOTE: The signature of this function must be kept in sync with
 // `RUST_CALLBACK_SIGNATURE`.
 unsafe extern "C" fn rust_callback<'a>(
     _: JNIEnv<'a>,
     _: JObject<'a>,
-    channel_ptr: jlong,
+    callback_ptr: jlong,
     error_code: jint,
-    help_code: jint,
+    authentication_type: jint,
 ) {
-    let channel = unsafe { Box::from_raw(channel_ptr as *mut Sender) };
+    let callback = unsafe { Box::from_raw(callback_ptr as *mut Callback) };
+    let channel = callback.sender;
 
     if error_code != 0 {
         let _ = channel.send(Err(match error_code {
@@ -49,23 +67,46 @@ unsafe extern "C" fn rust_callback<'a>(
             BIOMETRIC_ERROR_LOCKOUT_PERMANENT => Error::Exhausted,
             BIOMETRIC_ERROR_NO_BIOMETRICS => Error::Unavailable,
             BIOMETRIC_ERROR_NO_DEVICE_CREDENTIAL => Error::Unavailable,
-            BIOMETRIC_ERROR_NO_SPACE => Error::Unknown,
             BIOMETRIC_ERROR_SECURITY_UPDATE_REQUIRED => Error::UpdateRequired,
             BIOMETRIC_ERROR_TIMEOUT => Error::Timeout,
-            BIOMETRIC_ERROR_UNABLE_TO_PROCESS => Error::Unknown,
             BIOMETRIC_ERROR_USER_CANCELED => Error::UserCanceled,
-            BIOMETRIC_ERROR_VENDOR => Error::Unknown,
             BIOMETRIC_NO_AUTHENTICATION => Error::Unavailable,
-            _ => {
-                log::warn!("received unknown biometric error code: {error_code:#0x}");
-                Error::Unknown
+            BIOMETRIC_ERROR_NO_SPACE
+            | BIOMETRIC_ERROR_UNABLE_TO_PROCESS
+            | BIOMETRIC_ERROR_VENDOR => {
+                Error::Platform(PlatformError::BiometricPrompt { code: error_code })
             }
+            // Codes added in later versions of Android.
+            _ => Error::Platform(PlatformError::BiometricPrompt { code: error_code }),
+        }));
+    } else {
+        let _ = channel.send(Ok(match authentication_type {
+            AUTHENTICATION_RESULT_TYPE_BIOMETRIC => Some(AuthenticationMethod::Biometric),
+            AUTHENTICATION_RESULT_TYPE_DEVICE_CREDENTIAL => Some(AuthenticationMethod::Credential),
+            _ => None,
         }));
-    } else if help_code != 0 {
-        // TODO
-        let _ = channel.send(Err(Error::Unknown));
+    }
+}
+
+// NOTE: The signature of this function must be kept in sync with
+// `RUST_EVENT_SIGNATURE`.
+unsafe extern "C" fn rust_event<'a>(
+    mut env: JNIEnv<'a>,
+    _: JObject<'a>,
+    callback_ptr: jlong,
+    help: JString<'a>,
+) {
+    // The callback is only freed once the prompt reports its result, which
+    // happens after any events.
+    let callback = unsafe { &*(callback_ptr as *const Callback) };
+
+    if help.is_null() {
+        callback.events.emit(AuthEvent::AttemptFailed);
     } else {
-        let _ = channel.send(Ok(()));
+        match env.get_string(&help) {
+            Ok(help) => callback.events.emit(AuthEvent::Help(help.into())),
+            Err(e) => log::warn!("failed to read biometric help string: {e}"),
+        }
     }
 }
 
@@ -87,11 +128,18 @@ pub(super) fn get_callback_class(env: &mut JNIEnv<'_>) -> Result<&'static Global
 fn register_rust_callback<'a>(env: &mut JNIEnv<'a>, callback_class: &JClass<'a>) -> Result<()> {
     env.register_native_methods(
         callback_class,
-        &[NativeMethod {
-            name: "rustCallback".into(),
-            sig: RUST_CALLBACK_SIGNATURE.into(),
-            fn_ptr: rust_callback as *mut _,
-        }],
+        &[
+            NativeMethod {
+                name: "rustCallback".into(),
+                sig: RUST_CALLBACK_SIGNATURE.into(),
+                fn_ptr: rust_callback as *mut _,
+            },
+            NativeMethod {
+                name: "rustEvent".into(),
+                sig: RUST_EVENT_SIGNATURE.into(),
+                fn_ptr: rust_event as *mut _,
+            },
+        ],
     )
     .map_err(|e| e.into())
 }
@@ -143,6 +191,10 @@ const BIOMETRIC_ERROR_TIMEOUT: i32 = 3;
 const BIOMETRIC_ERROR_UNABLE_TO_PROCESS: i32 = 2;
 const BIOMETRIC_ERROR_USER_CANCELED: i32 = 0xa;
 const BIOMETRIC_ERROR_VENDOR: i32 = 8;
+// https://developer.android.com/reference/android/hardware/biometrics/BiometricPrompt#AUTHENTICATION_RESULT_TYPE_BIOMETRIC
+const AUTHENTICATION_RESULT_TYPE_BIOMETRIC: i32 = 2;
+const AUTHENTICATION_RESULT_TYPE_DEVICE_CREDENTIAL: i32 = 1;
+
 // NOTE: I don't think onAuthenticationError is ever actually called with this
 // value.
 const BIOMETRIC_NO_AUTHENTICATION: i32 = -1;
@@ -1,41 +1,73 @@
-use std::mem::MaybeUninit;
 #[cfg(not(feature = "async"))]
 use std::sync::mpsc as channel_impl;
+use std::{
+    borrow::Cow,
+    mem::MaybeUninit,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use block2::RcBlock;
 use objc2::rc::Retained;
 use objc2_foundation::{NSError, NSString};
-use objc2_local_authentication::{LAContext, LAError, LAPolicy};
+use objc2_local_authentication::{LABiometryType, LAContext, LAError, LAPolicy};
 #[cfg(feature = "async")]
 use tokio::sync::oneshot as channel_impl;
 
-use crate::{BiometricStrength, Error, Result, Text};
+use crate::{
+    event::EventHandler, ApplePolicy, Authentication, AuthenticationMethod, Availability,
+    BiometryKind, Error, PlatformError, PolicyError, PolicyMapping, Prompt, Result, Text,
+};
 
 pub(crate) type RawContext = ();
 
 #[derive(Debug)]
 pub(crate) struct Context {
-    inner: Retained<LAContext>,
+    /// The context is replaced when a handle invalidates it, so it's shared
+    /// with the handles.
+    inner: Arc<Mutex<Shared>>,
+    /// The number of times a handle has invalidated the context.
+    cancellations: Arc<AtomicUsize>,
 }
 
 impl Context {
     pub(crate) fn new(_: RawContext) -> Self {
         Self {
-            inner: unsafe { LAContext::new() },
+            inner: Arc::new(Mutex::new(Shared(unsafe { LAContext::new() }))),
+            cancellations: Arc::default(),
         }
     }
 
+    pub(crate) fn handle(&self) -> Handle {
+        Handle {
+            inner: self.inner.clone(),
+            cancellations: self.cancellations.clone(),
+        }
+    }
+
+    fn inner(&self) -> Retained<LAContext> {
+        self.inner.lock().unwrap().0.clone()
+    }
+
     #[cfg(feature = "async")]
     pub(crate) async fn authenticate(
         &self,
-        text: Text<'_, '_, '_, '_, '_, '_>,
+        text: Text<'_, '_, '_, '_, '_, '_, '_>,
         policy: &Policy,
-    ) -> Result<()> {
+        _: &EventHandler,
+    ) -> Result<Authentication> {
         // The callback should always execute and hence a message will always be sent.
         self.authenticate_inner(text, policy).await.unwrap()
     }
 
-    pub(crate) fn blocking_authenticate(&self, text: Text, policy: &Policy) -> Result<()> {
+    pub(crate) fn blocking_authenticate(
+        &self,
+        text: Text,
+        policy: &Policy,
+        _: &EventHandler,
+    ) -> Result<Authentication> {
         // The callback should always execute, hence a message will always be sent, and
         // hence it is ok to unwrap.
         #[cfg(feature = "async")]
@@ -54,49 +86,42 @@ impl Context {
 
     fn authenticate_inner(
         &self,
-        text: Text<'_, '_, '_, '_, '_, '_>,
+        text: Text<'_, '_, '_, '_, '_, '_, '_>,
         policy: &Policy,
-    ) -> channel_impl::Receiver<Result<()>> {
+    ) -> channel_impl::Receiver<Result<Authentication>> {
         let (tx, rx) = channel_impl::channel();
         let unsafe_tx = MaybeUninit::new(tx);
         let message = text.apple;
+        let method = method(policy.inner);
+        // The counter is read under the same lock handles invalidate the
+        // context with, so a cancellation can't fall between the two.
+        let (context, started) = {
+            let inner = self.inner.lock().unwrap();
+            (inner.0.clone(), self.cancellations.load(Ordering::SeqCst))
+        };
+        let cancellations = self.cancellations.clone();
 
         let block = RcBlock::new(move |is_success, error: *mut NSError| {
             // SAFETY: The callback is only executed once.
             let tx = unsafe { unsafe_tx.assume_init_read() };
             let _ = if bool::from(is_success) {
-                tx.send(Ok(()))
+                tx.send(Ok(Authentication::new(method, None, None)))
             } else {
-                let code = unsafe { &*error }.code();
-                #[allow(non_upper_case_globals)]
-                let error = match LAError(code) {
-                    LAError::AppCancel => Error::AppCanceled,
-                    LAError::AuthenticationFailed => Error::Authentication,
-                    LAError::BiometryDisconnected => Error::BiometryDisconnected,
-                    LAError::BiometryLockout => Error::Exhausted,
-                    // NOTE: This is triggered when access to biometrics is denied.
-                    LAError::BiometryNotAvailable => Error::Unavailable,
-                    LAError::BiometryNotEnrolled => Error::NotEnrolled,
-                    LAError::BiometryNotPaired => Error::NotPaired,
-                    // This error shouldn't occur, because we never invalidate the context.
-                    LAError::InvalidContext => Error::Unknown,
-                    LAError::InvalidDimensions => Error::InvalidDimensions,
-                    LAError::NotInteractive => Error::NotInteractive,
-                    LAError::PasscodeNotSet => Error::PasscodeNotSet,
-                    LAError::SystemCancel => Error::SystemCanceled,
-                    LAError::UserCancel => Error::UserCanceled,
-                    // TODO
-                    LAError::UserFallback => Error::Unknown,
-                    LAError::WatchNotAvailable => Error::WatchNotAvailable,
-                    _ => Error::Unknown,
-                };
-                tx.send(Err(error))
+                let error = unsafe { &*error };
+                // A handle invalidated the context after the evaluation
+                // started, which may be reported instead of `AppCancel`.
+                let cancelled = cancellations.load(Ordering::SeqCst) != started;
+                if LAError(error.code()) == LAError::InvalidContext && cancelled {
+                    tx.send(Err(Error::AppCanceled))
+                } else {
+                    tx.send(Err(convert(error)))
+                }
             };
         })
         .copy();
 
         unsafe {
-            self.inner.evaluatePolicy_localizedReason_reply(
+            context.evaluatePolicy_localizedReason_reply(
                 policy.inner,
                 &NSString::from_str(message),
                 &block,
@@ -105,105 +130,133 @@ impl Context {
 
         rx
     }
-}
 
-#[derive(Debug)]
-pub(crate) struct Policy {
-    inner: LAPolicy,
+    pub(crate) fn availability(&self, policy: &Policy) -> Result<Availability> {
+        let inner = self.inner();
+        let result =
+            unsafe { inner.canEvaluatePolicy_error(policy.inner) }.map_err(|error| convert(&error));
+
+        // The biometry type is only set after checking the policy.
+        #[allow(non_upper_case_globals)]
+        let biometry = match unsafe { inner.biometryType() } {
+            LABiometryType::TouchID => Some(BiometryKind::Fingerprint),
+            LABiometryType::FaceID => Some(BiometryKind::Face),
+            LABiometryType::OpticID => Some(BiometryKind::Iris),
+            _ => None,
+        };
+
+        Availability::from_result(result, biometry)
+    }
 }
 
 #[derive(Debug)]
-pub(crate) struct PolicyBuilder {
-    _biometrics: bool,
-    _password: bool,
-    _watch: bool,
-    _wrist_detection: bool,
+struct Shared(Retained<LAContext>);
+
+// SAFETY: The context is only used to evaluate policies and to invalidate them,
+// which LocalAuthentication allows from any thread.
+unsafe impl Send for Shared {}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Handle {
+    inner: Arc<Mutex<Shared>>,
+    cancellations: Arc<AtomicUsize>,
 }
 
-impl PolicyBuilder {
-    pub(crate) const fn new() -> Self {
-        Self {
-            _biometrics: true,
-            _password: true,
-            _watch: true,
-            _wrist_detection: true,
-        }
+impl Handle {
+    pub(crate) fn cancel(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        // Invalidating the context fails the evaluation in flight with
+        // `LAError::AppCancel` or `LAError::InvalidContext`, but also any later
+        // ones, so the context is replaced. The counter is only changed while
+        // the lock is held, so evaluations see it together with the context.
+        self.cancellations.fetch_add(1, Ordering::SeqCst);
+        unsafe { inner.0.invalidate() };
+        inner.0 = unsafe { LAContext::new() };
     }
+}
 
-    pub(crate) const fn biometrics(self, strength: Option<BiometricStrength>) -> Self {
-        Self {
-            _biometrics: strength.is_some(),
-            ..self
-        }
+/// Returns how the user authenticates using `policy`, if it only allows one
+/// method. LocalAuthentication doesn't report which one was used otherwise.
+#[allow(non_upper_case_globals)]
+fn method(policy: LAPolicy) -> Option<AuthenticationMethod> {
+    match policy {
+        LAPolicy::DeviceOwnerAuthenticationWithBiometrics => Some(AuthenticationMethod::Biometric),
+        LAPolicy::DeviceOwnerAuthenticationWithWatch => Some(AuthenticationMethod::Watch),
+        _ => None,
     }
+}
 
-    pub(crate) const fn password(self, password: bool) -> Self {
-        Self {
-            _password: password,
-            ..self
-        }
+#[allow(non_upper_case_globals)]
+fn convert(error: &NSError) -> Error {
+    match LAError(error.code()) {
+        LAError::AppCancel => Error::AppCanceled,
+        LAError::AuthenticationFailed => Error::Authentication,
+        LAError::BiometryDisconnected => Error::BiometryDisconnected,
+        LAError::BiometryLockout => Error::Exhausted,
+        // NOTE: This is triggered when access to biometrics is denied.
+        LAError::BiometryNotAvailable => Error::Unavailable,
+        LAError::BiometryNotEnrolled => Error::NotEnrolled,
+        LAError::BiometryNotPaired => Error::NotPaired,
+        // The context is only invalidated by `Handle::cancel`, after which the
+        // evaluation's callback reports this as `Error::AppCanceled` instead.
+        LAError::InvalidContext => platform(error),
+        LAError::InvalidDimensions => Error::InvalidDimensions,
+        LAError::NotInteractive => Error::NotInteractive,
+        LAError::PasscodeNotSet => Error::PasscodeNotSet,
+        LAError::SystemCancel => Error::SystemCanceled,
+        LAError::UserCancel => Error::UserCanceled,
+        LAError::UserFallback => Error::UserFallback,
+        LAError::WatchNotAvailable => Error::WatchNotAvailable,
+        _ => platform(error),
     }
+}
 
-    pub(crate) const fn watch(self, watch: bool) -> Self {
-        Self {
-            _watch: watch,
-            ..self
-        }
-    }
+fn platform(error: &NSError) -> Error {
+    Error::Platform(PlatformError::LocalAuthentication {
+        code: error.code(),
+        message: error.localized_description().to_string(),
+    })
+}
 
-    pub(crate) const fn wrist_detection(self, wrist_detection: bool) -> Self {
-        Self {
-            _wrist_detection: wrist_detection,
-            ..self
-        }
-    }
+#[derive(Clone, Debug)]
+pub(crate) struct Policy {
+    inner: LAPolicy,
+}
 
-    pub(crate) const fn build(self) -> Option<Policy> {
-        // TODO: Test watchos
-
-        #[cfg(target_os = "watchos")]
-        let policy = match self {
-            Self {
-                _password: true,
-                _wrist_detection: true,
-                ..
-            } => LAPolicy::DeviceOwnerAuthenticationWithWristDetection,
-            Self {
-                _password: true,
-                _wrist_detection: false,
-                ..
-            } => LAPolicy::DeviceOwnerAuthentication,
-            _ => return None,
+impl Policy {
+    pub(crate) const fn new(mapping: PolicyMapping, _: Option<&'static dyn Prompt>) -> Self {
+        let PolicyMapping::Apple(policy) = mapping else {
+            unreachable!()
         };
-
-        #[cfg(not(target_os = "watchos"))]
-        let policy = match self {
-            Self {
-                _biometrics: true,
-                _password: true,
-                _watch: true,
-                ..
-            } => LAPolicy::DeviceOwnerAuthentication,
-            Self {
-                _biometrics: true,
-                _password: false,
-                _watch: true,
-                ..
-            } => LAPolicy::DeviceOwnerAuthenticationWithBiometricsOrWatch,
-            Self {
-                _biometrics: true,
-                _password: false,
-                _watch: false,
-                ..
-            } => LAPolicy::DeviceOwnerAuthenticationWithBiometrics,
-            Self {
-                _biometrics: false,
-                _password: false,
-                _watch: true,
-                ..
-            } => LAPolicy::DeviceOwnerAuthenticationWithWatch,
-            _ => return None,
+        let inner = match policy {
+            ApplePolicy::DeviceOwnerAuthentication => LAPolicy::DeviceOwnerAuthentication,
+            ApplePolicy::DeviceOwnerAuthenticationWithBiometrics => {
+                LAPolicy::DeviceOwnerAuthenticationWithBiometrics
+            }
+            ApplePolicy::DeviceOwnerAuthenticationWithBiometricsOrWatch => {
+                LAPolicy::DeviceOwnerAuthenticationWithBiometricsOrWatch
+            }
+            ApplePolicy::DeviceOwnerAuthenticationWithWatch => {
+                LAPolicy::DeviceOwnerAuthenticationWithWatch
+            }
+            ApplePolicy::DeviceOwnerAuthenticationWithWristDetection => {
+                LAPolicy::DeviceOwnerAuthenticationWithWristDetection
+            }
         };
-        Some(Policy { inner: policy })
+        Self { inner }
+    }
+
+    pub(crate) fn with_action_id(
+        self,
+        _: Cow<'static, str>,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(self)
+    }
+
+    pub(crate) fn with_pam_service(
+        self,
+        _: Cow<'static, str>,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(self)
     }
 }
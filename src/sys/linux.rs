@@ -1,84 +1,454 @@
-//! This module is not public yet because it is a work in progress.
+mod agent;
+mod fprintd;
+mod pam;
+mod polkit;
+mod tty;
 
-use polkit::{Authority, CheckAuthorizationFlags, Details, UnixProcess};
+pub use self::{fprintd::Fprintd, pam::Pam, polkit::Polkit, tty::Tty};
 
-use crate::{BiometricStrength, Result};
+use std::{
+    borrow::Cow,
+    ffi::{CStr, CString},
+    mem::MaybeUninit,
+    ptr,
+    sync::{Arc, Mutex},
+};
 
-#[derive(Debug)]
-pub struct Policy;
+use gio::{
+    glib::{self, translate::ToGlibPtr},
+    prelude::*,
+};
+
+use crate::{
+    event::EventHandler, packaging::is_valid_action_id, target::is_valid_pam_service,
+    Authentication, Availability, AvailabilityStatus, BiometryKind, Error, PlatformError,
+    PolicyError, PolicyMapping, Prompt, Result, Text,
+};
+
+pub(crate) type RawContext = ();
 
 #[derive(Debug)]
-pub(crate) struct PolicyBuilder;
+pub(crate) struct Context {
+    request: RequestSlot,
+}
 
-impl PolicyBuilder {
-    pub(crate) const fn new() -> Self {
-        Self
+impl Context {
+    pub(crate) fn new(_: RawContext) -> Self {
+        Self {
+            request: RequestSlot::default(),
+        }
     }
 
-    pub(crate) const fn biometrics(self, _: Option<BiometricStrength>) -> Self {
-        Self
+    pub(crate) fn handle(&self) -> Handle {
+        Handle {
+            request: self.request.clone(),
+        }
     }
 
-    pub(crate) const fn password(self, _: bool) -> Self {
-        Self
+    fn begin(&self) -> Request<'_> {
+        self.request.begin()
     }
 
-    pub(crate) const fn watch(self, _: bool) -> Self {
-        Self
+    #[cfg(feature = "async")]
+    pub(crate) async fn authenticate(
+        &self,
+        text: Text<'_, '_, '_, '_, '_, '_, '_>,
+        policy: &Policy,
+        events: &EventHandler,
+    ) -> Result<Authentication> {
+        let request = self.begin();
+        let cancellable = &request.cancellable;
+        // Dropping the future cancels the request, which releases the
+        // fingerprint reader, or makes polkit dismiss the prompt.
+        let _guard = CancelOnDrop(cancellable.clone());
+
+        if policy.require_all {
+            let fingerprint = fprintd::authenticate_async(
+                text.linux.message,
+                policy.agent_prompt(),
+                cancellable.clone(),
+                events.clone(),
+            )
+            .await?;
+            let password = pam::authenticate_async(
+                text.linux.message,
+                policy.pam_service_or_default().clone(),
+                policy.prompt(),
+                cancellable.clone(),
+            )
+            .await?;
+            return Ok(fingerprint.then(password));
+        }
+        if let Some(prompt) = policy.fingerprint_prompt() {
+            match fprintd::authenticate_async(
+                text.linux.message,
+                prompt,
+                cancellable.clone(),
+                events.clone(),
+            )
+            .await
+            {
+                Err(Error::Unavailable | Error::NotEnrolled | Error::Authentication)
+                    if policy.password => {}
+                result => return result,
+            }
+        }
+        if let Some(service) = &policy.pam_service {
+            return pam::authenticate_async(
+                text.linux.message,
+                service.clone(),
+                policy.prompt(),
+                cancellable.clone(),
+            )
+            .await;
+        }
+        let prompt = policy.agent_prompt();
+        match polkit::authenticate_async(policy.action_id.clone(), prompt, cancellable.clone())
+            .await
+        {
+            Err(Error::Unavailable) if prompt.is_some() => {
+                pam::authenticate_async(
+                    text.linux.message,
+                    Cow::Borrowed(DEFAULT_PAM_SERVICE),
+                    policy.prompt(),
+                    cancellable.clone(),
+                )
+                .await
+            }
+            result => result,
+        }
     }
 
-    pub(crate) const fn wrist_detection(self, _: bool) -> Self {
-        Self
+    pub(crate) fn blocking_authenticate(
+        &self,
+        text: Text,
+        policy: &Policy,
+        events: &EventHandler,
+    ) -> Result<Authentication> {
+        let request = self.begin();
+        let cancellable = &request.cancellable;
+
+        if policy.require_all {
+            // The password is verified using PAM, as polkit may have kept a
+            // previous authorization.
+            let fingerprint = fprintd::authenticate(
+                text.linux.message,
+                policy.agent_prompt(),
+                Some(cancellable),
+                events,
+            )?;
+            let password = pam::authenticate(
+                text.linux.message,
+                policy.pam_service_or_default(),
+                policy.prompt(),
+                cancellable,
+            )?;
+            return Ok(fingerprint.then(password));
+        }
+        if let Some(prompt) = policy.fingerprint_prompt() {
+            // Fall back to a password if the user can't use their fingerprint,
+            // or it wasn't recognized.
+            match fprintd::authenticate(text.linux.message, prompt, Some(cancellable), events) {
+                Err(Error::Unavailable | Error::NotEnrolled | Error::Authentication)
+                    if policy.password => {}
+                result => return result,
+            }
+        }
+        if let Some(service) = &policy.pam_service {
+            return pam::authenticate(text.linux.message, service, policy.prompt(), cancellable);
+        }
+        // Without an authentication agent, e.g. over SSH, polkit's challenge is
+        // answered by our own agent. Only if polkit isn't running at all is the
+        // password verified using PAM directly, as it would otherwise bypass
+        // the action's authorization, e.g. if it requires an administrator.
+        let prompt = policy.agent_prompt();
+        match polkit::authenticate(&policy.action_id, prompt, cancellable) {
+            Err(Error::Unavailable) if prompt.is_some() => pam::authenticate(
+                text.linux.message,
+                DEFAULT_PAM_SERVICE,
+                policy.prompt(),
+                cancellable,
+            ),
+            result => result,
+        }
     }
 
-    pub(crate) const fn build(self) -> Option<Policy> {
-        Some(Policy)
+    pub(crate) fn availability(&self, policy: &Policy) -> Result<Availability> {
+        let mut biometrics = Err(Error::Unavailable);
+        let mut biometry = None;
+        if policy.biometrics {
+            biometrics = fprintd::check();
+            if matches!(biometrics, Ok(()) | Err(Error::NotEnrolled)) {
+                biometry = Some(BiometryKind::Fingerprint);
+            }
+        }
+        let availability = Availability::from_result(biometrics, biometry)?;
+        // The password is always verified using PAM if both are required, so
+        // only the fingerprint can be unavailable.
+        if availability.is_available() || !policy.password || policy.require_all {
+            return Ok(availability);
+        }
+
+        let password = if policy.pam_service.is_some() {
+            pam::check()
+        } else {
+            match polkit::check(&policy.action_id) {
+                Ok(Some(true)) => Ok(()),
+                Ok(Some(false)) => Err(Error::DisabledByPolicy),
+                // The policy file declaring the action isn't installed.
+                Ok(None) => Err(Error::Unavailable),
+                // The password is verified using PAM only if polkit isn't
+                // running.
+                Err(Error::Unavailable) if policy.agent_prompt().is_some() => pam::check(),
+                Err(e) => Err(e),
+            }
+        };
+        let password = Availability::from_result(password, biometry)?;
+        // If neither can be used, enrolling a fingerprint is more likely to be
+        // something the user can fix.
+        if password.is_available() || availability.status == AvailabilityStatus::Unavailable {
+            Ok(password)
+        } else {
+            Ok(availability)
+        }
     }
 }
 
-pub(crate) async fn authenticate(_message: &str, _: &Policy) -> Result<()> {
-    unimplemented!()
+#[derive(Clone, Debug)]
+pub(crate) struct Handle {
+    request: RequestSlot,
 }
 
-pub(crate) fn blocking_authenticate(_message: &str, _: &Policy) -> Result<()> {
-    // TODO: None?
-    let authority = Authority::sync(Option::<&gio::Cancellable>::None).unwrap();
-
-    let current_user = "klim";
-
-    let details = Details::new();
-    details.insert("user", Some(current_user));
-    // TODO: user.gecos
-    details.insert("user.display", Some(current_user));
-    // TODO: program
-    // TODO: command_line
-    details.insert("polkit.message", Some("Testing robius authentication"));
-    // TODO: polkit.gettext_domain
-
-    // for action in authority
-    //     .enumerate_actions_sync(Option::<&gio::Cancellable>::None)
-    //     .unwrap()
-    // {
-    //     println!("-- action --");
-    //     println!("id: {}", action.action_id());
-    //     println!("description: {}", action.description());
-    //     println!(
-    //         "allow_gui: {:?}",
-    //         action.annotation("org.freedesktop.policykit.exec.allow_gui")
-    //     );
-    // }
-
-    let subject = UnixProcess::new(std::process::id() as i32);
-    authority
-        .check_authorization_sync(
-            &subject,
-            "org.hello-world.authenticate",
-            Some(&details),
-            // None,
-            CheckAuthorizationFlags::ALLOW_USER_INTERACTION,
-            Option::<&gio::Cancellable>::None,
-        )
-        .unwrap();
-
-    Ok(())
+impl Handle {
+    pub(crate) fn cancel(&self) {
+        self.request.cancel();
+    }
+}
+
+/// The cancellable of a backend's request in flight, if any, which is shared
+/// with its handles.
+#[derive(Clone, Debug, Default)]
+struct RequestSlot(Arc<Mutex<Option<gio::Cancellable>>>);
+
+impl RequestSlot {
+    /// Starts a request that can be cancelled using [`cancel`](Self::cancel)
+    /// until the returned guard is dropped.
+    fn begin(&self) -> Request<'_> {
+        let cancellable = gio::Cancellable::new();
+        *self.0.lock().unwrap() = Some(cancellable.clone());
+        Request {
+            slot: &self.0,
+            cancellable,
+        }
+    }
+
+    fn cancel(&self) {
+        if let Some(cancellable) = &*self.0.lock().unwrap() {
+            cancellable.cancel();
+        }
+    }
+}
+
+/// A request in flight, which can no longer be cancelled once dropped.
+struct Request<'a> {
+    slot: &'a Mutex<Option<gio::Cancellable>>,
+    cancellable: gio::Cancellable,
+}
+
+impl Drop for Request<'_> {
+    fn drop(&mut self) {
+        let mut slot = self.slot.lock().unwrap();
+        // Another request may have started since.
+        if slot.as_ref() == Some(&self.cancellable) {
+            *slot = None;
+        }
+    }
+}
+
+/// Cancels the wrapped cancellable when the future owning it is dropped.
+#[cfg(feature = "async")]
+struct CancelOnDrop(gio::Cancellable);
+
+#[cfg(feature = "async")]
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// The PAM service used if polkit isn't available.
+const DEFAULT_PAM_SERVICE: &str = "login";
+
+#[derive(Clone, Debug)]
+pub(crate) struct Policy {
+    biometrics: bool,
+    password: bool,
+    action_id: Cow<'static, str>,
+    prompt: Option<&'static dyn Prompt>,
+    pam_service: Option<Cow<'static, str>>,
+    require_all: bool,
+}
+
+impl Policy {
+    pub(crate) const fn new(mapping: PolicyMapping, prompt: Option<&'static dyn Prompt>) -> Self {
+        let PolicyMapping::Linux(policy) = mapping else {
+            unreachable!()
+        };
+        Self {
+            biometrics: policy.biometrics,
+            password: policy.password,
+            action_id: Cow::Borrowed(policy.action_id),
+            prompt,
+            pam_service: match policy.pam_service {
+                Some(service) => Some(Cow::Borrowed(service)),
+                None => None,
+            },
+            require_all: policy.require_all,
+        }
+    }
+
+    pub(crate) fn with_action_id(
+        self,
+        action_id: Cow<'static, str>,
+    ) -> std::result::Result<Self, PolicyError> {
+        if !is_valid_action_id(&action_id) {
+            return Err(PolicyError::InvalidActionId);
+        }
+        Ok(Self { action_id, ..self })
+    }
+
+    pub(crate) fn with_pam_service(
+        self,
+        service: Cow<'static, str>,
+    ) -> std::result::Result<Self, PolicyError> {
+        if !is_valid_pam_service(&service) {
+            return Err(PolicyError::InvalidPamService);
+        }
+        Ok(Self {
+            pam_service: Some(service),
+            ..self
+        })
+    }
+
+    /// Returns the PAM service used to verify the password after the user's
+    /// fingerprint, if both are required.
+    fn pam_service_or_default(&self) -> &Cow<'static, str> {
+        const DEFAULT: &Cow<'static, str> = &Cow::Borrowed(DEFAULT_PAM_SERVICE);
+
+        self.pam_service.as_ref().unwrap_or(DEFAULT)
+    }
+
+    /// Returns the prompt used to ask for the password when verifying it
+    /// ourselves.
+    fn prompt(&self) -> &'static dyn Prompt {
+        self.prompt.unwrap_or(&tty::Terminal)
+    }
+
+    /// Returns the prompt our authentication agent uses, if polkit has no
+    /// other agent to show the prompt.
+    fn agent_prompt(&self) -> Option<&'static dyn Prompt> {
+        match self.prompt {
+            Some(prompt) => Some(prompt),
+            None if tty::Terminal::is_available() => Some(&tty::Terminal),
+            None => None,
+        }
+    }
+
+    /// Returns whether the user's fingerprint is verified using fprintd before
+    /// any password, and if so, the prompt that shows the reason for
+    /// authenticating while the reader waits.
+    ///
+    /// Without a prompt, nothing could be shown, so the password is verified
+    /// instead if the policy allows it, e.g. by polkit's agent, which may
+    /// offer the fingerprint itself.
+    fn fingerprint_prompt(&self) -> Option<Option<&'static dyn Prompt>> {
+        if !self.biometrics {
+            return None;
+        }
+        match self.agent_prompt() {
+            None if self.password => None,
+            prompt => Some(prompt),
+        }
+    }
+}
+
+impl From<glib::Error> for Error {
+    fn from(value: glib::Error) -> Self {
+        if let Some(error) = value.kind::<::polkit::Error>() {
+            match error {
+                ::polkit::Error::Cancelled => Self::AppCanceled,
+                ::polkit::Error::NotAuthorized => Self::Authentication,
+                ::polkit::Error::NotSupported => Self::Unavailable,
+                _ => Self::Platform(platform_error(&value)),
+            }
+        } else if value.matches(gio::IOErrorEnum::Cancelled) {
+            Self::AppCanceled
+        } else if value.matches(gio::IOErrorEnum::TimedOut) {
+            Self::Timeout
+        } else if let Some(name) = remote_error_name(&value) {
+            match name {
+                // The bus has no owner for the requested service, e.g. polkitd isn't
+                // installed.
+                "org.freedesktop.DBus.Error.ServiceUnknown"
+                | "org.freedesktop.DBus.Error.NameHasNoOwner" => Self::Unavailable,
+                _ => Self::Platform(platform_error(&value)),
+            }
+        } else if value.kind::<gio::IOErrorEnum>().is_some() {
+            // The bus itself couldn't be reached.
+            Self::Unavailable
+        } else {
+            Self::Platform(platform_error(&value))
+        }
+    }
+}
+
+fn platform_error(error: &glib::Error) -> PlatformError {
+    let raw: *const glib::ffi::GError = error.to_glib_none().0;
+    PlatformError::GLib {
+        domain: error.domain().as_str().to_string(),
+        code: unsafe { (*raw).code },
+        message: error.message().to_owned(),
+    }
+}
+
+/// Returns the name of the D-Bus error that caused `error`, if it was sent by
+/// a remote peer.
+fn remote_error_name(error: &glib::Error) -> Option<&str> {
+    error
+        .message()
+        .strip_prefix("GDBus.Error:")?
+        .split(':')
+        .next()
+}
+
+/// Returns the name of the user running the process, if it can be found.
+fn current_user() -> Option<String> {
+    let user = user_name(unsafe { libc::getuid() }).ok()?;
+    Some(user.to_string_lossy().into_owned())
+}
+
+/// Returns the name of the user with the given `uid`.
+fn user_name(uid: libc::uid_t) -> Result<CString> {
+    let mut buffer = vec![0; 1024];
+
+    loop {
+        let mut passwd = MaybeUninit::<libc::passwd>::uninit();
+        let mut entry = ptr::null_mut();
+        let err = unsafe {
+            libc::getpwuid_r(
+                uid,
+                passwd.as_mut_ptr(),
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut entry,
+            )
+        };
+
+        match err {
+            0 if entry.is_null() => return Err(Error::Unknown),
+            0 => return Ok(unsafe { CStr::from_ptr((*entry).pw_name) }.to_owned()),
+            libc::ERANGE => buffer.resize(buffer.len() * 2, 0),
+            _ => return Err(Error::Unknown),
+        }
+    }
 }
@@ -0,0 +1,379 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use gio::glib;
+use polkit::{Authority, Subject};
+
+use crate::{Error, Prompt, Result};
+
+/// The path agents are exported under, followed by a number that is unique
+/// within the process, so that authentications can run concurrently.
+const OBJECT_PATH: &str = "/rs/robius/authentication/AuthenticationAgent";
+
+const INTERFACE: &str = r#"
+<node>
+  <interface name="org.freedesktop.PolicyKit1.AuthenticationAgent">
+    <method name="BeginAuthentication">
+      <arg type="s" name="action_id" direction="in"/>
+      <arg type="s" name="message" direction="in"/>
+      <arg type="s" name="icon_name" direction="in"/>
+      <arg type="a{ss}" name="details" direction="in"/>
+      <arg type="s" name="cookie" direction="in"/>
+      <arg type="a(sa{sv})" name="identities" direction="in"/>
+    </method>
+    <method name="CancelAuthentication">
+      <arg type="s" name="cookie" direction="in"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+/// The locations polkit installs its setuid helper to on common distributions.
+const HELPERS: &[&str] = &[
+    "/usr/lib/polkit-1/polkit-agent-helper-1",
+    "/usr/libexec/polkit-agent-helper-1",
+    "/usr/lib/policykit-1/polkit-agent-helper-1",
+    "/run/wrappers/bin/polkit-agent-helper-1",
+];
+
+/// The variable overriding the location of the helper, so that tests can use
+/// a fake one.
+///
+/// As it decides which executable receives the user's password, it's only
+/// read with the `mock` feature, which is meant for tests.
+#[cfg(feature = "mock")]
+const HELPER_VAR: &str = "ROBIUS_POLKIT_AGENT_HELPER";
+
+/// The helpers that are running, keyed by the cookie of their authentication.
+type Sessions = Arc<Mutex<HashMap<String, Child>>>;
+
+/// A polkit authentication agent for the current process, which answers
+/// challenges using a [`Prompt`].
+///
+/// The password is verified by polkit's setuid helper, which reports the
+/// result to the authority, like the agents shipped with desktop environments.
+/// The agent is unregistered when dropped.
+pub(super) struct Agent<'a> {
+    authority: &'a Authority,
+    subject: &'a Subject,
+    path: String,
+    context: glib::MainContext,
+    main_loop: glib::MainLoop,
+    thread: Option<JoinHandle<()>>,
+    /// Whether the authority accepted the agent.
+    registered: bool,
+    /// The user whose password was last verified by the agent.
+    authenticated: Arc<Mutex<Option<String>>>,
+}
+
+impl<'a> Agent<'a> {
+    pub(super) fn register(
+        authority: &'a Authority,
+        subject: &'a Subject,
+        prompt: &'static dyn Prompt,
+    ) -> Result<Self> {
+        // Without the helper we couldn't verify the password anyway, so it's
+        // better to leave answering the challenge to someone else.
+        if helper().is_none() {
+            return Err(Error::Unavailable);
+        }
+        let connection = gio::bus_get_sync(gio::BusType::System, gio::Cancellable::NONE)?;
+        // Only the authority is allowed to start authentication.
+        let owner = authority.owner().ok_or(Error::Unavailable)?.to_string();
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let path = format!("{OBJECT_PATH}/{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+        let object_path = path.clone();
+        let authenticated = Arc::<Mutex<Option<String>>>::default();
+        let verified = authenticated.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        // Method calls are dispatched on the thread-default main context of the
+        // thread that exports the object, so the agent runs its own main loop
+        // while the caller waits for the authorization check.
+        let thread = thread::spawn(move || {
+            let context = glib::MainContext::new();
+            let main_loop = glib::MainLoop::new(Some(&context), false);
+
+            let _ = context.with_thread_default(|| {
+                let info = gio::DBusNodeInfo::for_xml(INTERFACE)
+                    .expect("invalid interface xml")
+                    .interfaces()[0]
+                    .clone();
+                let sessions = Sessions::default();
+
+                let registration = connection.register_object(
+                    &object_path,
+                    &info,
+                    move |_, sender, _, _, method, parameters, invocation| {
+                        if sender != owner {
+                            invocation.return_dbus_error(
+                                "org.freedesktop.PolicyKit1.Error.NotAuthorized",
+                                "only the authority can start authentication",
+                            );
+                            return;
+                        }
+                        match method {
+                            "BeginAuthentication" => begin(
+                                parameters,
+                                invocation,
+                                prompt,
+                                sessions.clone(),
+                                verified.clone(),
+                            ),
+                            "CancelAuthentication" => cancel(parameters, invocation, &sessions),
+                            _ => unreachable!(),
+                        }
+                    },
+                    |_, _, _, _, _| unreachable!(),
+                    |_, _, _, _, _, _| false,
+                );
+
+                match registration {
+                    Ok(id) => {
+                        let _ = tx.send(Ok((context.clone(), main_loop.clone())));
+                        main_loop.run();
+                        let _ = connection.unregister_object(id);
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(Error::from(e)));
+                    }
+                }
+            });
+        });
+
+        let (context, main_loop) = rx.recv().map_err(|_| Error::Unknown)??;
+        let mut agent = Self {
+            authority,
+            subject,
+            path,
+            context,
+            main_loop,
+            thread: Some(thread),
+            registered: false,
+            authenticated,
+        };
+        authority.register_authentication_agent_sync(
+            subject,
+            &locale(),
+            &agent.path,
+            gio::Cancellable::NONE,
+        )?;
+        agent.registered = true;
+
+        Ok(agent)
+    }
+
+    /// Returns the user whose password was verified, if the authority asked
+    /// the agent to authenticate anyone.
+    pub(super) fn authenticated_user(&self) -> Option<String> {
+        self.authenticated.lock().unwrap().clone()
+    }
+}
+
+impl Drop for Agent<'_> {
+    fn drop(&mut self) {
+        if self.registered {
+            let _ = self.authority.unregister_authentication_agent_sync(
+                self.subject,
+                &self.path,
+                gio::Cancellable::NONE,
+            );
+        }
+
+        // The loop is quit from a source on its own context, in case it hasn't
+        // started running yet.
+        let main_loop = self.main_loop.clone();
+        self.context.invoke(move || main_loop.quit());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn begin(
+    parameters: glib::Variant,
+    invocation: gio::DBusMethodInvocation,
+    prompt: &'static dyn Prompt,
+    sessions: Sessions,
+    authenticated: Arc<Mutex<Option<String>>>,
+) {
+    type Identity = (String, HashMap<String, glib::Variant>);
+    type Parameters = (
+        String,
+        String,
+        String,
+        HashMap<String, String>,
+        String,
+        Vec<Identity>,
+    );
+
+    let Some((_, message, _, _, cookie, identities)) = parameters.get::<Parameters>() else {
+        invocation.return_dbus_error("org.freedesktop.PolicyKit1.Error.Failed", "invalid request");
+        return;
+    };
+
+    // Prefer authenticating as the current user, but the action may require an
+    // administrator.
+    let current_uid = unsafe { libc::getuid() };
+    let uids = identities
+        .iter()
+        .filter(|(kind, _)| kind == "unix-user")
+        .filter_map(|(_, details)| details.get("uid")?.get::<u32>())
+        .collect::<Vec<_>>();
+    let Some(user) = uids
+        .iter()
+        .find(|uid| **uid == current_uid)
+        .or(uids.first())
+        .and_then(|uid| super::user_name(*uid).ok())
+    else {
+        invocation.return_dbus_error(
+            "org.freedesktop.PolicyKit1.Error.Failed",
+            "no identity to authenticate as",
+        );
+        return;
+    };
+
+    // The prompt may block, so the helper runs on a separate thread, and the
+    // result is sent back to this context to reply.
+    let (tx, rx) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    let mut invocation = Some(invocation);
+    rx.attach(
+        Some(&glib::MainContext::ref_thread_default()),
+        move |result: Result<()>| {
+            if let Some(invocation) = invocation.take() {
+                match result {
+                    Ok(()) => invocation.return_value(None),
+                    Err(Error::UserCanceled) => invocation.return_dbus_error(
+                        "org.freedesktop.PolicyKit1.Error.Cancelled",
+                        "authentication was canceled",
+                    ),
+                    Err(_) => invocation.return_dbus_error(
+                        "org.freedesktop.PolicyKit1.Error.Failed",
+                        "authentication failed",
+                    ),
+                }
+            }
+            glib::Continue(false)
+        },
+    );
+
+    thread::spawn(move || {
+        prompt.info(&message);
+        let user = user.to_string_lossy().into_owned();
+        let result = authenticate(&user, &cookie, prompt, &sessions);
+        if result.is_ok() {
+            *authenticated.lock().unwrap() = Some(user);
+        }
+        let _ = tx.send(result);
+    });
+}
+
+fn cancel(parameters: glib::Variant, invocation: gio::DBusMethodInvocation, sessions: &Sessions) {
+    if let Some((cookie,)) = parameters.get::<(String,)>() {
+        if let Some(child) = sessions.lock().unwrap().get_mut(&cookie) {
+            // The helper's output ends, failing the authentication.
+            let _ = child.kill();
+        }
+    }
+    invocation.return_value(None);
+}
+
+/// Authenticates `user` using polkit's helper, which reports the result of the
+/// authentication identified by `cookie` to the authority.
+fn authenticate(user: &str, cookie: &str, prompt: &dyn Prompt, sessions: &Sessions) -> Result<()> {
+    let mut child = Command::new(helper().ok_or(Error::Unavailable)?)
+        .arg(user)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|_| Error::Unavailable)?;
+    let stdin = child.stdin.take().ok_or(Error::Unknown)?;
+    let stdout = child.stdout.take().ok_or(Error::Unknown)?;
+    sessions.lock().unwrap().insert(cookie.to_owned(), child);
+
+    let result = converse(stdin, stdout, cookie, prompt);
+
+    if let Some(mut child) = sessions.lock().unwrap().remove(cookie) {
+        if result.is_err() {
+            let _ = child.kill();
+        }
+        let _ = child.wait();
+    }
+    result
+}
+
+/// Relays the helper's PAM conversation to `prompt`.
+fn converse(
+    mut stdin: ChildStdin,
+    stdout: ChildStdout,
+    cookie: &str,
+    prompt: &dyn Prompt,
+) -> Result<()> {
+    writeln!(stdin, "{cookie}").map_err(|_| Error::Unknown)?;
+
+    for line in BufReader::new(stdout).lines() {
+        let line = line.map_err(|_| Error::Unknown)?;
+
+        let response = if let Some(message) = line.strip_prefix("PAM_PROMPT_ECHO_OFF ") {
+            prompt.secret(message)?
+        } else if let Some(message) = line.strip_prefix("PAM_PROMPT_ECHO_ON ") {
+            prompt.text(message)?
+        } else if let Some(message) = line.strip_prefix("PAM_ERROR_MSG ") {
+            prompt.error(message);
+            continue;
+        } else if let Some(message) = line.strip_prefix("PAM_TEXT_INFO ") {
+            prompt.info(message);
+            continue;
+        } else if line == "SUCCESS" {
+            return Ok(());
+        } else if line == "FAILURE" {
+            return Err(Error::Authentication);
+        } else {
+            continue;
+        };
+
+        let mut response = response.into_bytes();
+        // The helper reads one response per line.
+        let result = if response.contains(&b'\n') {
+            Err(Error::Authentication)
+        } else {
+            response.push(b'\n');
+            stdin.write_all(&response).map_err(|_| Error::Unknown)
+        };
+        response.fill(0);
+        result?;
+    }
+
+    // The helper exited without a result, e.g. because the authentication was
+    // cancelled.
+    Err(Error::Authentication)
+}
+
+fn helper() -> Option<PathBuf> {
+    #[cfg(feature = "mock")]
+    if let Some(helper) = std::env::var_os(HELPER_VAR) {
+        return Some(helper.into());
+    }
+    HELPERS
+        .iter()
+        .map(PathBuf::from)
+        .find(|helper| helper.exists())
+}
+
+/// Returns the locale used to translate the authority's messages.
+fn locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|locale| !locale.is_empty())
+        .unwrap_or_else(|| "C".to_owned())
+}
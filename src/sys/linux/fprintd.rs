@@ -0,0 +1,352 @@
+use std::{cell::RefCell, rc::Rc};
+
+use gio::{glib, prelude::*};
+
+#[cfg(feature = "async")]
+use super::CancelOnDrop;
+use super::RequestSlot;
+#[cfg(feature = "async")]
+use crate::BoxFuture;
+use crate::{
+    event::EventHandler, AuthEvent, Authentication, AuthenticationMethod, AuthenticationRequest,
+    Authenticator, Availability, BiometryKind, Error, Policy, Prompt, Result,
+};
+
+const SERVICE: &str = "net.reactivated.Fprint";
+const MANAGER_PATH: &str = "/net/reactivated/Fprint/Manager";
+const MANAGER_INTERFACE: &str = "net.reactivated.Fprint.Manager";
+const DEVICE_INTERFACE: &str = "net.reactivated.Fprint.Device";
+
+/// Shown once the reader is ready to scan the user's finger.
+const SCAN_MESSAGE: &str = "Place your finger on the fingerprint reader";
+
+/// Verifies the user's fingerprint using the default reader known to
+/// [`fprintd`](https://fprint.freedesktop.org/).
+///
+/// Once the reader is ready, the reason for authenticating is shown using the
+/// policy's [`Prompt`], or the process's controlling terminal, if any. Policies
+/// that don't allow biometrics fail with [`Error::Unavailable`].
+#[derive(Debug, Default)]
+pub struct Fprintd {
+    request: RequestSlot,
+}
+
+impl Fprintd {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Authenticator for Fprintd {
+    fn blocking_authenticate(&self, request: AuthenticationRequest<'_>) -> Result<Authentication> {
+        if request.policy().biometrics().is_none() {
+            return Err(Error::Unavailable);
+        }
+        let guard = self.request.begin();
+        authenticate(
+            request.text().linux.message,
+            request.policy().inner.agent_prompt(),
+            Some(&guard.cancellable),
+            request.events,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    fn authenticate<'a>(
+        &'a self,
+        request: AuthenticationRequest<'a>,
+    ) -> BoxFuture<'a, Result<Authentication>> {
+        Box::pin(async move {
+            if request.policy().biometrics().is_none() {
+                return Err(Error::Unavailable);
+            }
+            let guard = self.request.begin();
+            let _cancel = CancelOnDrop(guard.cancellable.clone());
+            authenticate_async(
+                request.text().linux.message,
+                request.policy().inner.agent_prompt(),
+                guard.cancellable.clone(),
+                request.events.clone(),
+            )
+            .await
+        })
+    }
+
+    fn availability(&self, policy: &Policy) -> Result<Availability> {
+        if policy.biometrics().is_none() {
+            return Availability::from_result(Err(Error::Unavailable), None);
+        }
+        let result = check();
+        let biometry =
+            matches!(result, Ok(()) | Err(Error::NotEnrolled)).then_some(BiometryKind::Fingerprint);
+        Availability::from_result(result, biometry)
+    }
+
+    #[inline]
+    fn probe(&self) -> Result<()> {
+        check()
+    }
+
+    #[inline]
+    fn cancel(&self) {
+        self.request.cancel();
+    }
+}
+
+/// Verifies any of the current user's enrolled fingerprints using the default
+/// fingerprint reader.
+///
+/// Once the reader is ready, `message` and an instruction to scan a finger are
+/// shown using `prompt`, if any. Cancelling `cancellable` stops the
+/// verification and returns [`Error::AppCanceled`]. Scans the user should retry
+/// are reported to `events`.
+pub(super) fn authenticate(
+    message: &str,
+    prompt: Option<&dyn Prompt>,
+    cancellable: Option<&gio::Cancellable>,
+    events: &EventHandler,
+) -> Result<Authentication> {
+    let connection = gio::bus_get_sync(gio::BusType::System, cancellable)?;
+
+    let (device,) = call(
+        &connection,
+        MANAGER_PATH,
+        MANAGER_INTERFACE,
+        "GetDefaultDevice",
+        None,
+        cancellable,
+    )?
+    .get::<(glib::variant::ObjectPath,)>()
+    .ok_or(Error::Unknown)?;
+    let device = device.as_str();
+
+    // An empty username claims the device for the caller.
+    call(
+        &connection,
+        device,
+        DEVICE_INTERFACE,
+        "Claim",
+        Some(&("",).to_variant()),
+        cancellable,
+    )?;
+    let prompt = prompt.map(|prompt| (prompt, message));
+    let result = verify(&connection, device, prompt, cancellable, events);
+    let _ = call(
+        &connection,
+        device,
+        DEVICE_INTERFACE,
+        "Release",
+        None,
+        gio::Cancellable::NONE,
+    );
+
+    // The device was claimed for the current user, whose fingerprints were
+    // verified.
+    result.map(|()| {
+        Authentication::new(
+            Some(AuthenticationMethod::Biometric),
+            None,
+            super::current_user(),
+        )
+    })
+}
+
+/// Checks whether the current user has enrolled fingerprints on the default
+/// fingerprint reader, without claiming it.
+pub(super) fn check() -> Result<()> {
+    let connection = gio::bus_get_sync(gio::BusType::System, gio::Cancellable::NONE)?;
+
+    let (device,) = call(
+        &connection,
+        MANAGER_PATH,
+        MANAGER_INTERFACE,
+        "GetDefaultDevice",
+        None,
+        gio::Cancellable::NONE,
+    )?
+    .get::<(glib::variant::ObjectPath,)>()
+    .ok_or(Error::Unknown)?;
+
+    // An empty username lists the caller's fingerprints.
+    let (fingers,) = call(
+        &connection,
+        device.as_str(),
+        DEVICE_INTERFACE,
+        "ListEnrolledFingers",
+        Some(&("",).to_variant()),
+        gio::Cancellable::NONE,
+    )?
+    .get::<(Vec<String>,)>()
+    .ok_or(Error::Unknown)?;
+
+    if fingers.is_empty() {
+        Err(Error::NotEnrolled)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+pub(super) async fn authenticate_async(
+    message: &str,
+    prompt: Option<&'static dyn Prompt>,
+    cancellable: gio::Cancellable,
+    events: EventHandler,
+) -> Result<Authentication> {
+    let message = message.to_owned();
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    std::thread::spawn(move || {
+        let _ = tx.send(authenticate(&message, prompt, Some(&cancellable), &events));
+    });
+
+    rx.await.unwrap_or(Err(Error::Unknown))
+}
+
+fn verify(
+    connection: &gio::DBusConnection,
+    device: &str,
+    prompt: Option<(&dyn Prompt, &str)>,
+    cancellable: Option<&gio::Cancellable>,
+    events: &EventHandler,
+) -> Result<()> {
+    let context = glib::MainContext::new();
+    let main_loop = glib::MainLoop::new(Some(&context), false);
+
+    context
+        .with_thread_default(|| {
+            let status = Rc::new(RefCell::new(None));
+
+            // The signal is dispatched on the thread-default main context, so
+            // subscribing before starting the verification ensures no statuses
+            // are missed.
+            let subscription = {
+                let status = status.clone();
+                let main_loop = main_loop.clone();
+                let events = events.clone();
+                connection.signal_subscribe(
+                    Some(SERVICE),
+                    Some(DEVICE_INTERFACE),
+                    Some("VerifyStatus"),
+                    Some(device),
+                    None,
+                    gio::DBusSignalFlags::NONE,
+                    move |_, _, _, _, _, parameters| {
+                        let Some((result, done)) = parameters.get::<(String, bool)>() else {
+                            return;
+                        };
+                        match convert(&result, done) {
+                            Status::Done(result) => {
+                                *status.borrow_mut() = Some(result);
+                                main_loop.quit();
+                            }
+                            Status::Retry(event) => events.emit(event),
+                        }
+                    },
+                )
+            };
+            // The cancellable may be cancelled from another thread before the main
+            // loop starts running, so the loop is quit from a source on its own
+            // context.
+            let cancelled = cancellable.and_then(|cancellable| {
+                let context = context.clone();
+                let main_loop = main_loop.clone();
+                cancellable.connect_cancelled(move |_| {
+                    let main_loop = main_loop.clone();
+                    context.invoke(move || main_loop.quit());
+                })
+            });
+
+            let result = call(
+                connection,
+                device,
+                DEVICE_INTERFACE,
+                "VerifyStart",
+                Some(&("any",).to_variant()),
+                cancellable,
+            )
+            .map(|_| {
+                if let Some((prompt, message)) = prompt {
+                    prompt.info(message);
+                    prompt.info(SCAN_MESSAGE);
+                }
+                main_loop.run();
+                status.take().unwrap_or(Err(Error::AppCanceled))
+            })
+            .and_then(|result| {
+                let _ = call(
+                    connection,
+                    device,
+                    DEVICE_INTERFACE,
+                    "VerifyStop",
+                    None,
+                    gio::Cancellable::NONE,
+                );
+                result
+            });
+
+            if let (Some(cancellable), Some(cancelled)) = (cancellable, cancelled) {
+                cancellable.disconnect_cancelled(cancelled);
+            }
+            connection.signal_unsubscribe(subscription);
+
+            result
+        })
+        .map_err(|_| Error::Unknown)?
+}
+
+/// The status reported by a `VerifyStatus` signal.
+enum Status {
+    Done(Result<()>),
+    /// The user should scan their finger again.
+    Retry(AuthEvent),
+}
+
+/// Converts the result of a `VerifyStatus` signal.
+fn convert(result: &str, done: bool) -> Status {
+    let event = match result {
+        "verify-match" => return Status::Done(Ok(())),
+        "verify-no-match" => AuthEvent::AttemptFailed,
+        "verify-retry-scan" => AuthEvent::RetryScan,
+        "verify-swipe-too-short" => AuthEvent::SwipeTooShort,
+        "verify-finger-not-centered" => AuthEvent::FingerNotCentered,
+        "verify-remove-and-retry" => AuthEvent::RemoveAndRetry,
+        "verify-disconnected" => return Status::Done(Err(Error::BiometryDisconnected)),
+        _ => return Status::Done(Err(Error::Unknown)),
+    };
+    if done {
+        Status::Done(Err(Error::Authentication))
+    } else {
+        Status::Retry(event)
+    }
+}
+
+fn call(
+    connection: &gio::DBusConnection,
+    path: &str,
+    interface: &str,
+    method: &str,
+    parameters: Option<&glib::Variant>,
+    cancellable: Option<&gio::Cancellable>,
+) -> Result<glib::Variant> {
+    connection
+        .call_sync(
+            Some(SERVICE),
+            path,
+            interface,
+            method,
+            parameters,
+            None,
+            gio::DBusCallFlags::NONE,
+            -1,
+            cancellable,
+        )
+        .map_err(|error| match super::remote_error_name(&error) {
+            Some("net.reactivated.Fprint.Error.NoSuchDevice") => Error::Unavailable,
+            Some("net.reactivated.Fprint.Error.NoEnrolledPrints") => Error::NotEnrolled,
+            Some("net.reactivated.Fprint.Error.AlreadyInUse") => Error::Busy,
+            Some("net.reactivated.Fprint.Error.PermissionDenied") => Error::Authentication,
+            _ => error.into(),
+        })
+}
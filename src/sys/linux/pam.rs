@@ -0,0 +1,482 @@
+#[cfg(feature = "async")]
+use std::borrow::Cow;
+use std::{
+    ffi::{c_char, c_int, c_void, CStr, CString},
+    mem, ptr,
+    sync::OnceLock,
+};
+
+use gio::prelude::*;
+
+#[cfg(feature = "async")]
+use super::CancelOnDrop;
+use super::RequestSlot;
+#[cfg(feature = "async")]
+use crate::BoxFuture;
+use crate::{
+    Authentication, AuthenticationMethod, AuthenticationRequest, Authenticator, Availability,
+    Error, PlatformError, Policy, Prompt, Result,
+};
+
+const PAM_SUCCESS: c_int = 0;
+const PAM_OPEN_ERR: c_int = 1;
+const PAM_SYMBOL_ERR: c_int = 2;
+const PAM_SERVICE_ERR: c_int = 3;
+const PAM_BUF_ERR: c_int = 5;
+const PAM_PERM_DENIED: c_int = 6;
+const PAM_AUTH_ERR: c_int = 7;
+const PAM_CRED_INSUFFICIENT: c_int = 8;
+const PAM_AUTHINFO_UNAVAIL: c_int = 9;
+const PAM_USER_UNKNOWN: c_int = 10;
+const PAM_MAXTRIES: c_int = 11;
+const PAM_NEW_AUTHTOK_REQD: c_int = 12;
+const PAM_ACCT_EXPIRED: c_int = 13;
+const PAM_CONV_ERR: c_int = 19;
+const PAM_MODULE_UNKNOWN: c_int = 28;
+
+const PAM_USER: c_int = 2;
+
+const PAM_DISALLOW_NULL_AUTHTOK: c_int = 0x1;
+
+const PAM_PROMPT_ECHO_OFF: c_int = 1;
+const PAM_PROMPT_ECHO_ON: c_int = 2;
+const PAM_ERROR_MSG: c_int = 3;
+const PAM_TEXT_INFO: c_int = 4;
+
+/// Verifies the user's password using PAM.
+///
+/// The policy's PAM service (see
+/// [`PolicyBuilder::pam_service`](crate::PolicyBuilder::pam_service)) is used,
+/// or `login` if it doesn't have one. The password is asked for using the
+/// policy's [`Prompt`], or the process's controlling terminal. Policies that
+/// don't allow passwords fail with [`Error::Unavailable`], as does every
+/// request if libpam isn't installed.
+#[derive(Debug, Default)]
+pub struct Pam {
+    request: RequestSlot,
+    /// The prompt used instead of the policy's.
+    prompt: Option<&'static dyn Prompt>,
+}
+
+impl Pam {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a backend that asks for the password using `prompt`, whichever
+    /// prompt the policy has.
+    pub(super) fn with_prompt(prompt: &'static dyn Prompt) -> Self {
+        Self {
+            request: RequestSlot::default(),
+            prompt: Some(prompt),
+        }
+    }
+
+    fn prompt(&self, policy: &super::Policy) -> &'static dyn Prompt {
+        self.prompt.unwrap_or_else(|| policy.prompt())
+    }
+}
+
+impl Authenticator for Pam {
+    fn blocking_authenticate(&self, request: AuthenticationRequest<'_>) -> Result<Authentication> {
+        let policy = &request.policy().inner;
+        if !policy.password {
+            return Err(Error::Unavailable);
+        }
+        let guard = self.request.begin();
+        authenticate(
+            request.text().linux.message,
+            policy.pam_service_or_default(),
+            self.prompt(policy),
+            &guard.cancellable,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    fn authenticate<'a>(
+        &'a self,
+        request: AuthenticationRequest<'a>,
+    ) -> BoxFuture<'a, Result<Authentication>> {
+        Box::pin(async move {
+            let policy = &request.policy().inner;
+            if !policy.password {
+                return Err(Error::Unavailable);
+            }
+            let guard = self.request.begin();
+            let _cancel = CancelOnDrop(guard.cancellable.clone());
+            authenticate_async(
+                request.text().linux.message,
+                policy.pam_service_or_default().clone(),
+                self.prompt(policy),
+                guard.cancellable.clone(),
+            )
+            .await
+        })
+    }
+
+    fn availability(&self, policy: &Policy) -> Result<Availability> {
+        // Whether the service accepts the password can't be checked without
+        // asking for it.
+        let result = if policy.password() {
+            check()
+        } else {
+            Err(Error::Unavailable)
+        };
+        Availability::from_result(result, None)
+    }
+
+    #[inline]
+    fn probe(&self) -> Result<()> {
+        check()
+    }
+
+    #[inline]
+    fn cancel(&self) {
+        self.request.cancel();
+    }
+}
+
+#[repr(C)]
+struct PamMessage {
+    msg_style: c_int,
+    msg: *const c_char,
+}
+
+#[repr(C)]
+struct PamResponse {
+    resp: *mut c_char,
+    resp_retcode: c_int,
+}
+
+type ConvFn = unsafe extern "C" fn(
+    num_msg: c_int,
+    msg: *mut *const PamMessage,
+    resp: *mut *mut PamResponse,
+    appdata_ptr: *mut c_void,
+) -> c_int;
+
+#[repr(C)]
+struct PamConv {
+    conv: Option<ConvFn>,
+    appdata_ptr: *mut c_void,
+}
+
+#[repr(C)]
+struct PamHandle {
+    _private: [u8; 0],
+}
+
+type StartFn = unsafe extern "C" fn(
+    service_name: *const c_char,
+    user: *const c_char,
+    pam_conversation: *const PamConv,
+    pamh: *mut *mut PamHandle,
+) -> c_int;
+type StartConfdirFn = unsafe extern "C" fn(
+    service_name: *const c_char,
+    user: *const c_char,
+    pam_conversation: *const PamConv,
+    confdir: *const c_char,
+    pamh: *mut *mut PamHandle,
+) -> c_int;
+type EndFn = unsafe extern "C" fn(pamh: *mut PamHandle, pam_status: c_int) -> c_int;
+type AuthenticateFn = unsafe extern "C" fn(pamh: *mut PamHandle, flags: c_int) -> c_int;
+type StrerrorFn = unsafe extern "C" fn(pamh: *mut PamHandle, errnum: c_int) -> *const c_char;
+type GetItemFn = unsafe extern "C" fn(
+    pamh: *const PamHandle,
+    item_type: c_int,
+    item: *mut *const c_void,
+) -> c_int;
+
+/// The functions used from libpam, which is loaded when first used so that
+/// processes that never use PAM don't depend on it.
+struct Library {
+    start: StartFn,
+    /// Only available since Linux-PAM 1.4.
+    start_confdir: Option<StartConfdirFn>,
+    end: EndFn,
+    authenticate: AuthenticateFn,
+    acct_mgmt: AuthenticateFn,
+    strerror: StrerrorFn,
+    get_item: GetItemFn,
+}
+
+impl Library {
+    /// Returns libpam, or [`Error::Unavailable`] if it isn't installed.
+    fn get() -> Result<&'static Self> {
+        static LIBRARY: OnceLock<Option<Library>> = OnceLock::new();
+
+        LIBRARY
+            .get_or_init(|| unsafe { Self::load() })
+            .as_ref()
+            .ok_or(Error::Unavailable)
+    }
+
+    unsafe fn load() -> Option<Self> {
+        // The library is never closed, so the functions stay valid.
+        let handle = libc::dlopen(c"libpam.so.0".as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+        if handle.is_null() {
+            return None;
+        }
+        let symbol = |name: &CStr| {
+            let symbol = libc::dlsym(handle, name.as_ptr());
+            (!symbol.is_null()).then_some(symbol)
+        };
+
+        Some(Self {
+            start: mem::transmute::<*mut c_void, StartFn>(symbol(c"pam_start")?),
+            start_confdir: symbol(c"pam_start_confdir")
+                .map(|symbol| mem::transmute::<*mut c_void, StartConfdirFn>(symbol)),
+            end: mem::transmute::<*mut c_void, EndFn>(symbol(c"pam_end")?),
+            authenticate: mem::transmute::<*mut c_void, AuthenticateFn>(symbol(
+                c"pam_authenticate",
+            )?),
+            acct_mgmt: mem::transmute::<*mut c_void, AuthenticateFn>(symbol(c"pam_acct_mgmt")?),
+            strerror: mem::transmute::<*mut c_void, StrerrorFn>(symbol(c"pam_strerror")?),
+            get_item: mem::transmute::<*mut c_void, GetItemFn>(symbol(c"pam_get_item")?),
+        })
+    }
+}
+
+/// Checks whether libpam is installed.
+pub(super) fn check() -> Result<()> {
+    Library::get().map(|_| ())
+}
+
+/// Verifies the current user's credentials using the PAM `service`,
+/// conversing with the user through `prompt`.
+///
+/// `service` is either the name of a service configured in `/etc/pam.d`, or an
+/// absolute path to a service's configuration file, which requires Linux-PAM
+/// 1.4 or later. PAM can't be interrupted, so cancelling `cancellable` only
+/// takes effect before the next message.
+///
+/// Fails with [`Error::Unavailable`] if libpam isn't installed, or is too old
+/// to load the service from a path.
+pub(super) fn authenticate(
+    message: &str,
+    service: &str,
+    prompt: &dyn Prompt,
+    cancellable: &gio::Cancellable,
+) -> Result<Authentication> {
+    let library = Library::get()?;
+    let user = super::user_name(unsafe { libc::getuid() })?;
+    let (confdir, service) = match service.rsplit_once('/') {
+        Some((confdir, name)) if service.starts_with('/') => {
+            let confdir = if confdir.is_empty() { "/" } else { confdir };
+            (Some(to_c_string(confdir)?), to_c_string(name)?)
+        }
+        _ => (None, to_c_string(service)?),
+    };
+
+    let mut conversation = Conversation {
+        prompt,
+        cancellable,
+        error: None,
+    };
+    let conv = PamConv {
+        conv: Some(converse),
+        appdata_ptr: &mut conversation as *mut Conversation as *mut c_void,
+    };
+
+    let mut handle = ptr::null_mut();
+    let status = unsafe {
+        match (confdir, library.start_confdir) {
+            (Some(confdir), Some(start_confdir)) => start_confdir(
+                service.as_ptr(),
+                user.as_ptr(),
+                &conv,
+                confdir.as_ptr(),
+                &mut handle,
+            ),
+            (Some(_), None) => return Err(Error::Unavailable),
+            (None, _) => (library.start)(service.as_ptr(), user.as_ptr(), &conv, &mut handle),
+        }
+    };
+    if status != PAM_SUCCESS {
+        return Err(convert(library, status));
+    }
+
+    prompt.info(message);
+
+    let mut status = unsafe { (library.authenticate)(handle, PAM_DISALLOW_NULL_AUTHTOK) };
+    if status == PAM_SUCCESS {
+        status = unsafe { (library.acct_mgmt)(handle, PAM_DISALLOW_NULL_AUTHTOK) };
+    }
+    // Modules can change the user being authenticated, so we check that the
+    // credentials belonged to the current user.
+    if status == PAM_SUCCESS && !is_authenticated_user(library, handle, &user) {
+        status = PAM_AUTH_ERR;
+    }
+    unsafe { (library.end)(handle, status) };
+
+    if cancellable.is_cancelled() {
+        Err(Error::AppCanceled)
+    } else if status == PAM_SUCCESS {
+        Ok(Authentication::new(
+            Some(AuthenticationMethod::Credential),
+            None,
+            Some(user.to_string_lossy().into_owned()),
+        ))
+    } else {
+        // The prompt's error takes precedence over how the module reported it.
+        Err(conversation
+            .error
+            .unwrap_or_else(|| convert(library, status)))
+    }
+}
+
+#[cfg(feature = "async")]
+pub(super) async fn authenticate_async(
+    message: &str,
+    service: Cow<'static, str>,
+    prompt: &'static dyn Prompt,
+    cancellable: gio::Cancellable,
+) -> Result<Authentication> {
+    let message = message.to_owned();
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    // PAM modules block while conversing with the user, so they run on a
+    // separate thread rather than blocking the caller's executor.
+    std::thread::spawn(move || {
+        let _ = tx.send(authenticate(&message, &service, prompt, &cancellable));
+    });
+
+    rx.await.unwrap_or(Err(Error::Unknown))
+}
+
+struct Conversation<'a> {
+    prompt: &'a dyn Prompt,
+    cancellable: &'a gio::Cancellable,
+    /// The error returned by the prompt, if it failed.
+    error: Option<Error>,
+}
+
+unsafe extern "C" fn converse(
+    num_msg: c_int,
+    msg: *mut *const PamMessage,
+    resp: *mut *mut PamResponse,
+    appdata_ptr: *mut c_void,
+) -> c_int {
+    let conversation = &mut *(appdata_ptr as *mut Conversation);
+    let Ok(len) = usize::try_from(num_msg) else {
+        return PAM_CONV_ERR;
+    };
+
+    // PAM frees the responses, so they must be allocated using malloc.
+    let responses = libc::calloc(len, std::mem::size_of::<PamResponse>()) as *mut PamResponse;
+    if responses.is_null() {
+        return PAM_BUF_ERR;
+    }
+
+    for i in 0..len {
+        let message = &**msg.add(i);
+        let text = CStr::from_ptr(message.msg).to_string_lossy();
+
+        let response = match message.msg_style {
+            // The request was cancelled while PAM was busy.
+            _ if conversation.cancellable.is_cancelled() => Err(Error::AppCanceled),
+            PAM_PROMPT_ECHO_OFF => conversation.prompt.secret(&text).map(Some),
+            PAM_PROMPT_ECHO_ON => conversation.prompt.text(&text).map(Some),
+            PAM_ERROR_MSG => {
+                conversation.prompt.error(&text);
+                Ok(None)
+            }
+            PAM_TEXT_INFO => {
+                conversation.prompt.info(&text);
+                Ok(None)
+            }
+            _ => Err(Error::Unknown),
+        };
+
+        match response.and_then(|response| response.map(into_malloced).transpose()) {
+            Ok(Some(response)) => (*responses.add(i)).resp = response,
+            Ok(None) => {}
+            Err(e) => {
+                conversation.error = Some(e);
+                free_responses(responses, i);
+                return PAM_CONV_ERR;
+            }
+        }
+    }
+
+    *resp = responses;
+    PAM_SUCCESS
+}
+
+/// Copies `response` into a buffer allocated using malloc, clearing the
+/// original.
+fn into_malloced(mut response: String) -> Result<*mut c_char> {
+    let result = if response.contains('\0') {
+        // A password can't contain a nul byte.
+        Err(Error::Authentication)
+    } else {
+        let copy = unsafe { libc::malloc(response.len() + 1) } as *mut c_char;
+        if copy.is_null() {
+            Err(Error::Unknown)
+        } else {
+            unsafe {
+                ptr::copy_nonoverlapping(response.as_ptr().cast(), copy, response.len());
+                *copy.add(response.len()) = 0;
+            }
+            Ok(copy)
+        }
+    };
+
+    // SAFETY: Nul bytes are valid UTF-8.
+    unsafe { response.as_bytes_mut() }.fill(0);
+    result
+}
+
+/// Clears and frees the first `len` responses, and the array containing them.
+unsafe fn free_responses(responses: *mut PamResponse, len: usize) {
+    for i in 0..len {
+        let response = (*responses.add(i)).resp;
+        if !response.is_null() {
+            ptr::write_bytes(response, 0, libc::strlen(response));
+            libc::free(response.cast());
+        }
+    }
+    libc::free(responses.cast());
+}
+
+fn is_authenticated_user(library: &Library, handle: *mut PamHandle, user: &CStr) -> bool {
+    let mut item = ptr::null();
+    let status = unsafe { (library.get_item)(handle, PAM_USER, &mut item) };
+    status == PAM_SUCCESS && !item.is_null() && unsafe { CStr::from_ptr(item.cast()) } == user
+}
+
+fn to_c_string(value: &str) -> Result<CString> {
+    CString::new(value).map_err(|_| Error::Unknown)
+}
+
+fn convert(library: &Library, status: c_int) -> Error {
+    match status {
+        PAM_AUTH_ERR
+        | PAM_PERM_DENIED
+        | PAM_CRED_INSUFFICIENT
+        | PAM_USER_UNKNOWN
+        | PAM_NEW_AUTHTOK_REQD
+        | PAM_ACCT_EXPIRED => Error::Authentication,
+        PAM_MAXTRIES => Error::Exhausted,
+        // The service or one of its modules couldn't be loaded.
+        PAM_AUTHINFO_UNAVAIL | PAM_OPEN_ERR | PAM_SYMBOL_ERR | PAM_SERVICE_ERR
+        | PAM_MODULE_UNKNOWN => Error::Unavailable,
+        _ => {
+            // Linux-PAM doesn't use the handle to describe the status.
+            let message = unsafe { (library.strerror)(ptr::null_mut(), status) };
+            let message = if message.is_null() {
+                String::new()
+            } else {
+                unsafe { CStr::from_ptr(message) }
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            Error::Platform(PlatformError::Pam {
+                code: status,
+                message,
+            })
+        }
+    }
+}
@@ -0,0 +1,292 @@
+#[cfg(feature = "async")]
+use std::borrow::Cow;
+
+use gio::glib;
+use polkit::{
+    Authority, AuthorizationResult, CheckAuthorizationFlags, ImplicitAuthorization, Subject,
+    UnixProcess,
+};
+
+#[cfg(feature = "async")]
+use super::CancelOnDrop;
+use super::{agent::Agent, RequestSlot};
+#[cfg(feature = "async")]
+use crate::BoxFuture;
+use crate::{
+    Authentication, AuthenticationMethod, AuthenticationRequest, Authenticator, Availability,
+    Error, Policy, Prompt, Result,
+};
+
+/// Verifies the user's password by asking
+/// [`polkit`](https://www.freedesktop.org/software/polkit/docs/latest/polkit.8.html)
+/// to authorize the policy's action.
+///
+/// If polkit has no authentication agent, our own agent asks for the password
+/// as described in the [crate documentation](crate#linux). Unlike
+/// [`SystemAuthenticator`](crate::SystemAuthenticator), the password isn't
+/// verified using PAM if polkit isn't running. Policies that don't allow
+/// passwords fail with [`Error::Unavailable`].
+#[derive(Debug, Default)]
+pub struct Polkit {
+    request: RequestSlot,
+}
+
+impl Polkit {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Authenticator for Polkit {
+    fn blocking_authenticate(&self, request: AuthenticationRequest<'_>) -> Result<Authentication> {
+        let policy = &request.policy().inner;
+        if !policy.password {
+            return Err(Error::Unavailable);
+        }
+        let guard = self.request.begin();
+        authenticate(&policy.action_id, policy.agent_prompt(), &guard.cancellable)
+    }
+
+    #[cfg(feature = "async")]
+    fn authenticate<'a>(
+        &'a self,
+        request: AuthenticationRequest<'a>,
+    ) -> BoxFuture<'a, Result<Authentication>> {
+        Box::pin(async move {
+            let policy = &request.policy().inner;
+            if !policy.password {
+                return Err(Error::Unavailable);
+            }
+            let guard = self.request.begin();
+            let _cancel = CancelOnDrop(guard.cancellable.clone());
+            authenticate_async(
+                policy.action_id.clone(),
+                policy.agent_prompt(),
+                guard.cancellable.clone(),
+            )
+            .await
+        })
+    }
+
+    fn availability(&self, policy: &Policy) -> Result<Availability> {
+        let result = if policy.password() {
+            match check(&policy.inner.action_id) {
+                Ok(Some(true)) => Ok(()),
+                Ok(Some(false)) => Err(Error::DisabledByPolicy),
+                // The policy file declaring the action isn't installed.
+                Ok(None) => Err(Error::Unavailable),
+                Err(e) => Err(e),
+            }
+        } else {
+            Err(Error::Unavailable)
+        };
+        Availability::from_result(result, None)
+    }
+
+    fn probe(&self) -> Result<()> {
+        // Listing the actions starts polkit if it's activatable.
+        authority(gio::Cancellable::NONE)?
+            .enumerate_actions_sync(gio::Cancellable::NONE)
+            .map_err(convert_error)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn cancel(&self) {
+        self.request.cancel();
+    }
+}
+
+/// Asks polkit to authorize `action_id`.
+///
+/// If polkit has no authentication agent to show the prompt and `prompt` is
+/// set, our own agent is registered for the duration of the check. Cancelling
+/// `cancellable` makes polkit dismiss the prompt.
+///
+/// No details are passed with the check, as polkit refuses them from callers
+/// other than root and the action's owner, so the prompt shows the message
+/// declared in the action's policy file.
+pub(super) fn authenticate(
+    action_id: &str,
+    prompt: Option<&'static dyn Prompt>,
+    cancellable: &gio::Cancellable,
+) -> Result<Authentication> {
+    let authority = authority(Some(cancellable))?;
+    let subject = UnixProcess::new(std::process::id() as i32);
+    let check = || {
+        authority
+            .check_authorization_sync(
+                &subject,
+                action_id,
+                None,
+                CheckAuthorizationFlags::ALLOW_USER_INTERACTION,
+                Some(cancellable),
+            )
+            .map_err(convert_error)
+    };
+
+    let result = check()?;
+    let agent = match prompt {
+        Some(prompt) if needs_agent(&result) => Some(register_agent(&authority, &subject, prompt)?),
+        _ => None,
+    };
+    match agent {
+        Some(agent) => convert(&check()?, Some(&agent)),
+        None => convert(&result, None),
+    }
+}
+
+#[cfg(feature = "async")]
+pub(super) async fn authenticate_async(
+    action_id: Cow<'static, str>,
+    prompt: Option<&'static dyn Prompt>,
+    cancellable: gio::Cancellable,
+) -> Result<Authentication> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    // polkit invokes the callback on the thread-default main context of the
+    // thread that started the check, so we run a main loop on a separate thread
+    // rather than blocking the caller's executor.
+    std::thread::spawn(move || {
+        let context = glib::MainContext::new();
+        let main_loop = glib::MainLoop::new(Some(&context), false);
+
+        let result = context.with_thread_default(|| {
+            let authority = authority(Some(&cancellable))?;
+            let subject = UnixProcess::new(std::process::id() as i32);
+
+            let check = || -> Result<AuthorizationResult> {
+                let (result_tx, result_rx) = std::sync::mpsc::channel();
+                let quit = main_loop.clone();
+                authority.check_authorization(
+                    &subject,
+                    &action_id,
+                    None,
+                    CheckAuthorizationFlags::ALLOW_USER_INTERACTION,
+                    Some(&cancellable),
+                    move |result| {
+                        let _ = result_tx.send(result);
+                        quit.quit();
+                    },
+                );
+                main_loop.run();
+
+                // The callback is always invoked, even if the check is cancelled.
+                result_rx
+                    .recv()
+                    .map_err(|_| Error::Unknown)?
+                    .map_err(convert_error)
+            };
+
+            let result = check()?;
+            let agent = match prompt {
+                Some(prompt) if needs_agent(&result) => {
+                    Some(register_agent(&authority, &subject, prompt)?)
+                }
+                _ => None,
+            };
+            match agent {
+                Some(agent) => convert(&check()?, Some(&agent)),
+                None => convert(&result, None),
+            }
+        });
+
+        let _ = tx.send(result.unwrap_or(Err(Error::Unknown)));
+    });
+
+    rx.await.unwrap_or(Err(Error::Unknown))
+}
+
+/// Returns whether `action_id` is declared, and whether active sessions may be
+/// authorized to perform it.
+///
+/// Rules can still deny authorization, so this is only a best guess.
+pub(super) fn check(action_id: &str) -> Result<Option<bool>> {
+    let authority = authority(gio::Cancellable::NONE)?;
+    let action = authority
+        .enumerate_actions_sync(gio::Cancellable::NONE)
+        .map_err(convert_error)?
+        .into_iter()
+        .find(|action| action.action_id() == action_id);
+
+    Ok(action.map(|action| action.implicit_active() != ImplicitAuthorization::NotAuthorized))
+}
+
+/// Registers our agent to answer the challenge for `subject`.
+///
+/// If the agent can't be used, e.g. because polkit's helper isn't installed,
+/// nobody can show the prompt, so this fails with [`Error::NotInteractive`]
+/// rather than [`Error::Unavailable`], which would mean polkit isn't running.
+fn register_agent<'a>(
+    authority: &'a Authority,
+    subject: &'a Subject,
+    prompt: &'static dyn Prompt,
+) -> Result<Agent<'a>> {
+    Agent::register(authority, subject, prompt).map_err(|e| match e {
+        Error::Unavailable => Error::NotInteractive,
+        e => e,
+    })
+}
+
+/// Returns polkit's authority.
+///
+/// Fails with [`Error::Unavailable`] if the system bus can't be reached, in
+/// which case polkit can't be running either.
+fn authority(cancellable: Option<&gio::Cancellable>) -> Result<Authority> {
+    Ok(Authority::sync(cancellable)?)
+}
+
+/// Converts an error returned by polkit.
+///
+/// Only errors meaning that polkit isn't running are converted to
+/// [`Error::Unavailable`], so that callers can tell when the action's
+/// authorization can't be enforced by anyone.
+fn convert_error(error: glib::Error) -> Error {
+    let unreachable = match super::remote_error_name(&error) {
+        Some(name) => matches!(
+            name,
+            "org.freedesktop.DBus.Error.ServiceUnknown"
+                | "org.freedesktop.DBus.Error.NameHasNoOwner"
+        ),
+        None => false,
+    };
+    match Error::from(error.clone()) {
+        Error::Unavailable if !unreachable => Error::Platform(super::platform_error(&error)),
+        e => e,
+    }
+}
+
+/// Returns whether polkit could only authorize the action if there was an
+/// authentication agent to show the prompt.
+fn needs_agent(result: &AuthorizationResult) -> bool {
+    result.is_challenge() && !is_dismissed(result)
+}
+
+/// Converts the result of a check, made while `agent` was registered if set.
+fn convert(result: &AuthorizationResult, agent: Option<&Agent>) -> Result<Authentication> {
+    if result.is_authorized() {
+        // Other agents don't tell us how they authenticated the user, and the
+        // action may not have required authentication at all.
+        let account = agent.and_then(Agent::authenticated_user);
+        let method = account.as_ref().map(|_| AuthenticationMethod::Credential);
+        Ok(Authentication::new(method, None, account))
+    } else if is_dismissed(result) {
+        Err(Error::UserCanceled)
+    } else if result.is_challenge() {
+        // We always allow user interaction, so polkit only responds with a
+        // challenge if there is no authentication agent to show the prompt.
+        Err(Error::NotInteractive)
+    } else {
+        Err(Error::Authentication)
+    }
+}
+
+// `AuthorizationResult::is_dismissed` isn't exposed by the polkit crate, so we
+// read the detail it's based on ourselves.
+fn is_dismissed(result: &AuthorizationResult) -> bool {
+    result
+        .details()
+        .and_then(|details| details.lookup("polkit.dismissed"))
+        .is_some()
+}
@@ -0,0 +1,233 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    mem::MaybeUninit,
+    os::fd::AsRawFd,
+};
+
+use super::pam::Pam;
+#[cfg(feature = "async")]
+use crate::BoxFuture;
+use crate::{
+    Authentication, AuthenticationRequest, Authenticator, Availability, Error, Policy, Prompt,
+    Result,
+};
+
+const CTRL_C: u8 = 0x03;
+const CTRL_D: u8 = 0x04;
+const CTRL_U: u8 = 0x15;
+const BACKSPACE: u8 = 0x08;
+const DELETE: u8 = 0x7f;
+
+/// Verifies the user's password using PAM, asking for it on the process's
+/// controlling terminal instead of using the policy's [`Prompt`].
+///
+/// The PAM service is chosen in the same way as by [`Pam`]. The backend can
+/// only be used if the process has a controlling terminal.
+#[derive(Debug)]
+pub struct Tty {
+    pam: Pam,
+}
+
+impl Default for Tty {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            pam: Pam::with_prompt(&Terminal),
+        }
+    }
+}
+
+impl Tty {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Authenticator for Tty {
+    #[inline]
+    fn blocking_authenticate(&self, request: AuthenticationRequest<'_>) -> Result<Authentication> {
+        self.pam.blocking_authenticate(request)
+    }
+
+    #[inline]
+    #[cfg(feature = "async")]
+    fn authenticate<'a>(
+        &'a self,
+        request: AuthenticationRequest<'a>,
+    ) -> BoxFuture<'a, Result<Authentication>> {
+        self.pam.authenticate(request)
+    }
+
+    fn availability(&self, policy: &Policy) -> Result<Availability> {
+        if Terminal::is_available() {
+            self.pam.availability(policy)
+        } else {
+            Availability::from_result(Err(Error::Unavailable), None)
+        }
+    }
+
+    fn probe(&self) -> Result<()> {
+        if Terminal::is_available() {
+            self.pam.probe()
+        } else {
+            Err(Error::NotInteractive)
+        }
+    }
+
+    #[inline]
+    fn cancel(&self) {
+        self.pam.cancel();
+    }
+}
+
+/// A prompt on the process's controlling terminal.
+///
+/// The terminal is opened for every message, so the prompt can be used from
+/// any thread.
+pub(super) struct Terminal;
+
+impl Terminal {
+    /// Returns whether the process has a controlling terminal.
+    pub(super) fn is_available() -> bool {
+        open().is_ok()
+    }
+}
+
+impl Prompt for Terminal {
+    fn secret(&self, message: &str) -> Result<String> {
+        read_line(message, false)
+    }
+
+    fn text(&self, message: &str) -> Result<String> {
+        read_line(message, true)
+    }
+
+    fn info(&self, message: &str) {
+        if let Ok(mut tty) = open() {
+            let _ = writeln!(tty, "{message}");
+        }
+    }
+
+    fn error(&self, message: &str) {
+        self.info(message);
+    }
+}
+
+fn open() -> Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(|_| Error::NotInteractive)
+}
+
+/// Reads a line from the terminal after writing `message`, optionally
+/// echoing the user's input.
+///
+/// The terminal is put into non-canonical mode without signals, so that
+/// Ctrl-C cancels the prompt rather than killing the process.
+fn read_line(message: &str, echo: bool) -> Result<String> {
+    let mut tty = open()?;
+    let _mode = RawMode::enable(&tty)?;
+
+    write!(tty, "{message}").map_err(|_| Error::NotInteractive)?;
+    let _ = tty.flush();
+
+    let mut line = Line(Vec::new());
+    let result = loop {
+        let mut byte = 0;
+        match tty.read(std::slice::from_mut(&mut byte)) {
+            Ok(1) => {}
+            _ => break Err(Error::NotInteractive),
+        }
+
+        match byte {
+            b'\r' | b'\n' => break Ok(()),
+            CTRL_C => break Err(Error::UserCanceled),
+            CTRL_D if line.0.is_empty() => break Err(Error::UserCanceled),
+            BACKSPACE | DELETE => {
+                if line.pop() && echo {
+                    let _ = tty.write_all(b"\x08 \x08");
+                }
+            }
+            CTRL_U => {
+                while line.pop() {
+                    if echo {
+                        let _ = tty.write_all(b"\x08 \x08");
+                    }
+                }
+            }
+            byte if byte.is_ascii_control() => {}
+            byte => {
+                line.0.push(byte);
+                if echo {
+                    let _ = tty.write_all(&[byte]);
+                }
+            }
+        }
+    };
+    let _ = tty.write_all(b"\n");
+
+    result?;
+    String::from_utf8(std::mem::take(&mut line.0)).map_err(|e| {
+        e.into_bytes().fill(0);
+        Error::Authentication
+    })
+}
+
+/// A line of input, which is cleared when dropped.
+struct Line(Vec<u8>);
+
+impl Line {
+    /// Removes the last character, returning whether there was one.
+    fn pop(&mut self) -> bool {
+        let Some(len) = self.0.iter().rposition(|byte| byte & 0xc0 != 0x80) else {
+            return false;
+        };
+        self.0[len..].fill(0);
+        self.0.truncate(len);
+        true
+    }
+}
+
+impl Drop for Line {
+    fn drop(&mut self) {
+        self.0.fill(0);
+    }
+}
+
+/// Restores the terminal's original mode when dropped.
+struct RawMode {
+    fd: i32,
+    original: libc::termios,
+}
+
+impl RawMode {
+    fn enable(tty: &File) -> Result<Self> {
+        let fd = tty.as_raw_fd();
+        let mut original = MaybeUninit::uninit();
+        if unsafe { libc::tcgetattr(fd, original.as_mut_ptr()) } != 0 {
+            return Err(Error::NotInteractive);
+        }
+        let original = unsafe { original.assume_init() };
+
+        let mut raw = original;
+        raw.c_lflag &= !(libc::ECHO | libc::ICANON | libc::ISIG);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        // Input that was typed ahead is kept, unlike with TCSAFLUSH.
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+            return Err(Error::NotInteractive);
+        }
+
+        Ok(Self { fd, original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, &self.original) };
+    }
+}
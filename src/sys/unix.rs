@@ -0,0 +1,19 @@
+use std::{mem::MaybeUninit, time::Duration};
+
+/// The clock that keeps advancing while the system is asleep.
+///
+/// On Apple targets, this is `CLOCK_MONOTONIC`, unlike `CLOCK_UPTIME_RAW`,
+/// which `Instant` uses.
+#[cfg(target_vendor = "apple")]
+const CLOCK: libc::clockid_t = libc::CLOCK_MONOTONIC;
+#[cfg(not(target_vendor = "apple"))]
+const CLOCK: libc::clockid_t = libc::CLOCK_BOOTTIME;
+
+/// Returns the time since the system booted, including time spent suspended.
+pub(crate) fn uptime() -> Duration {
+    let mut time = MaybeUninit::<libc::timespec>::uninit();
+    let err = unsafe { libc::clock_gettime(CLOCK, time.as_mut_ptr()) };
+    assert_eq!(err, 0, "failed to read the clock");
+    let time = unsafe { time.assume_init() };
+    Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
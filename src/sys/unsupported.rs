@@ -1,4 +1,13 @@
-use crate::{BiometricStrength, Error, Result, Text};
+use std::{
+    borrow::Cow,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    event::EventHandler, Authentication, Availability, Error, PolicyError, PolicyMapping, Prompt,
+    Result, Text,
+};
 
 pub(crate) type RawContext = ();
 
@@ -10,48 +19,68 @@ impl Context {
         Self
     }
 
+    pub(crate) fn handle(&self) -> Handle {
+        Handle
+    }
+
     #[cfg(feature = "async")]
     pub(crate) async fn authenticate(
         &self,
-        _: Text<'_, '_, '_, '_, '_, '_>,
+        _: Text<'_, '_, '_, '_, '_, '_, '_>,
         _: &Policy,
-    ) -> Result<()> {
+        _: &EventHandler,
+    ) -> Result<Authentication> {
         Err(Error::Unknown)
     }
 
-    pub(crate) fn blocking_authenticate(&self, _: Text, _: &Policy) -> Result<()> {
+    pub(crate) fn blocking_authenticate(
+        &self,
+        _: Text,
+        _: &Policy,
+        _: &EventHandler,
+    ) -> Result<Authentication> {
         Err(Error::Unknown)
     }
+
+    pub(crate) fn availability(&self, _: &Policy) -> Result<Availability> {
+        Availability::from_result(Err(Error::Unavailable), None)
+    }
 }
 
-#[derive(Debug)]
-pub(crate) struct Policy;
+#[derive(Clone, Debug)]
+pub(crate) struct Handle;
 
-#[derive(Debug)]
-pub(crate) struct PolicyBuilder;
+impl Handle {
+    pub(crate) fn cancel(&self) {}
+}
 
-impl PolicyBuilder {
-    pub(crate) const fn new() -> Self {
-        Self
-    }
+#[derive(Clone, Debug)]
+pub(crate) struct Policy;
 
-    pub(crate) const fn biometrics(self, _: Option<BiometricStrength>) -> Self {
+impl Policy {
+    pub(crate) const fn new(_: PolicyMapping, _: Option<&'static dyn Prompt>) -> Self {
         Self
     }
 
-    pub(crate) const fn password(self, _: bool) -> Self {
-        Self
+    pub(crate) fn with_action_id(
+        self,
+        _: Cow<'static, str>,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(self)
     }
 
-    pub(crate) const fn watch(self, _: bool) -> Self {
-        Self
+    pub(crate) fn with_pam_service(
+        self,
+        _: Cow<'static, str>,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(self)
     }
+}
 
-    pub(crate) const fn wrist_detection(self, _: bool) -> Self {
-        Self
-    }
+/// Returns the time since the process started, as the system's uptime can't
+/// be read.
+pub(crate) fn uptime() -> Duration {
+    static START: OnceLock<Instant> = OnceLock::new();
 
-    pub(crate) const fn build(self) -> Option<Policy> {
-        None
-    }
+    START.get_or_init(Instant::now).elapsed()
 }
@@ -1,99 +1,161 @@
 mod fallback;
 
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
 use windows::{
     core::HSTRING,
-    Foundation::IAsyncOperation,
+    Foundation::{AsyncStatus, IAsyncOperation},
     Security::Credentials::UI::{
         UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
     },
+    Win32::System::SystemInformation::GetTickCount64,
 };
 
-use crate::{text::WindowsText, BiometricStrength, Error, Result, Text};
+use crate::{
+    event::EventHandler, text::WindowsText, Authentication, Availability, Error, PlatformError,
+    PolicyError, PolicyMapping, Prompt, Result, Text,
+};
 
 pub(crate) type RawContext = ();
 
+type Verification = IAsyncOperation<UserConsentVerificationResult>;
+
 #[derive(Debug)]
-pub(crate) struct Context;
+pub(crate) struct Context {
+    /// The verification in flight, if any.
+    request: Arc<Mutex<Option<Verification>>>,
+}
 
 impl Context {
     pub(crate) fn new(_: RawContext) -> Self {
-        Self
+        Self {
+            request: Arc::default(),
+        }
+    }
+
+    pub(crate) fn handle(&self) -> Handle {
+        Handle {
+            request: self.request.clone(),
+        }
     }
 
     #[cfg(feature = "async")]
     pub(crate) async fn authenticate(
         &self,
-        message: Text<'_, '_, '_, '_, '_, '_>,
+        message: Text<'_, '_, '_, '_, '_, '_, '_>,
         _: &Policy,
-    ) -> Result<()> {
+        _: &EventHandler,
+    ) -> Result<Authentication> {
         // NOTE: If we don't check availability, `request_verification` will hang.
         let available =
             check_availability()?.await == Ok(UserConsentVerifierAvailability::Available);
 
         if available {
-            convert(request_verification(message.windows)?.await?)
+            let verification = request_verification(message.windows)?;
+            let _request = self.begin(&verification);
+            result(&verification, verification.clone().await)
         } else {
             fallback::authenticate(message.windows)
         }
     }
 
-    pub(crate) fn blocking_authenticate(&self, message: Text, _: &Policy) -> Result<()> {
+    pub(crate) fn blocking_authenticate(
+        &self,
+        message: Text,
+        _: &Policy,
+        _: &EventHandler,
+    ) -> Result<Authentication> {
         // NOTE: If we don't check availability, `request_verification` will hang.
         let available =
             check_availability()?.get() == Ok(UserConsentVerifierAvailability::Available);
 
         if available {
             let verification = request_verification(message.windows)?;
-            convert(verification.get()?)
+            let _request = self.begin(&verification);
+            result(&verification, verification.get())
         } else {
             fallback::authenticate(message.windows)
         }
     }
-}
 
-#[derive(Debug)]
-pub(crate) struct Policy;
+    /// Makes `verification` cancellable using the context's handles until the
+    /// returned guard is dropped.
+    fn begin(&self, verification: &Verification) -> Request<'_> {
+        *self.request.lock().unwrap() = Some(verification.clone());
+        Request {
+            slot: &self.request,
+            verification: verification.clone(),
+        }
+    }
 
-#[derive(Debug)]
-pub(crate) struct PolicyBuilder {
-    valid: bool,
+    pub(crate) fn availability(&self, _: &Policy) -> Result<Availability> {
+        // If Windows Hello isn't available, e.g. because group policy disabled
+        // it or no biometric device is configured, the password is verified
+        // using the credential prompt instead, so authentication is always
+        // available and Windows Hello's own status is only checked for errors.
+        // Windows Hello can use a fingerprint, face, or iris, and doesn't say
+        // which.
+        check_availability()?.get()?;
+        Availability::from_result(Ok(()), None)
+    }
 }
 
-impl PolicyBuilder {
-    pub(crate) const fn new() -> Self {
-        Self { valid: true }
-    }
+#[derive(Clone, Debug)]
+pub(crate) struct Handle {
+    request: Arc<Mutex<Option<Verification>>>,
+}
 
-    pub(crate) const fn biometrics(self, biometrics: Option<BiometricStrength>) -> Self {
-        if biometrics.is_none() {
-            Self { valid: false }
-        } else {
-            self
+impl Handle {
+    pub(crate) fn cancel(&self) {
+        if let Some(verification) = &*self.request.lock().unwrap() {
+            let _ = verification.Cancel();
         }
     }
+}
 
-    pub(crate) const fn password(self, password: bool) -> Self {
-        if password {
-            self
-        } else {
-            Self { valid: false }
+/// A verification in flight, which can no longer be cancelled once dropped.
+struct Request<'a> {
+    slot: &'a Mutex<Option<Verification>>,
+    verification: Verification,
+}
+
+impl Drop for Request<'_> {
+    fn drop(&mut self) {
+        let mut slot = self.slot.lock().unwrap();
+        // Another verification may have started since.
+        if slot.as_ref() == Some(&self.verification) {
+            *slot = None;
         }
     }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Policy;
 
-    pub(crate) const fn watch(self, _: bool) -> Self {
-        self
+impl Policy {
+    pub(crate) const fn new(mapping: PolicyMapping, _: Option<&'static dyn Prompt>) -> Self {
+        let PolicyMapping::Windows = mapping else {
+            unreachable!()
+        };
+        Self
     }
 
-    pub(crate) const fn wrist_detection(self, _: bool) -> Self {
-        self
+    pub(crate) fn with_action_id(
+        self,
+        _: Cow<'static, str>,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(self)
     }
 
-    pub(crate) const fn build(self) -> Option<Policy> {
-        if self.valid {
-            Some(Policy)
-        } else {
-            None
-        }
+    pub(crate) fn with_pam_service(
+        self,
+        _: Cow<'static, str>,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(self)
     }
 }
 
@@ -102,9 +164,7 @@ fn check_availability() -> Result<IAsyncOperation<UserConsentVerifierAvailabilit
 }
 
 #[cfg(feature = "uwp")]
-fn request_verification(
-    text: WindowsText,
-) -> Result<IAsyncOperation<UserConsentVerificationResult>> {
+fn request_verification(text: WindowsText) -> Result<Verification> {
     let caption = caption(text.description);
 
     UserConsentVerifier::RequestVerificationAsync(&HSTRING::from_wide(&caption[..])?)
@@ -112,9 +172,7 @@ fn request_verification(
 }
 
 #[cfg(not(feature = "uwp"))]
-fn request_verification(
-    text: WindowsText,
-) -> Result<IAsyncOperation<UserConsentVerificationResult>> {
+fn request_verification(text: WindowsText) -> Result<Verification> {
     use windows::{
         core::{factory, s},
         Win32::{
@@ -211,6 +269,20 @@ fn caption(message: &str) -> Vec<u16> {
     caption
 }
 
+/// Converts the outcome of `verification`.
+fn result(
+    verification: &Verification,
+    result: windows::core::Result<UserConsentVerificationResult>,
+) -> Result<Authentication> {
+    match result {
+        // Windows Hello doesn't say whether the user used a biometric or
+        // their PIN.
+        Ok(result) => convert(result).map(|()| Authentication::new(None, None, None)),
+        Err(_) if verification.Status() == Ok(AsyncStatus::Canceled) => Err(Error::AppCanceled),
+        Err(e) => Err(e.into()),
+    }
+}
+
 fn convert(result: UserConsentVerificationResult) -> Result<()> {
     match result {
         UserConsentVerificationResult::Verified => Ok(()),
@@ -225,7 +297,15 @@ fn convert(result: UserConsentVerificationResult) -> Result<()> {
 }
 
 impl From<windows::core::Error> for Error {
-    fn from(_value: windows::core::Error) -> Self {
-        Self::Unknown
+    fn from(value: windows::core::Error) -> Self {
+        Self::Platform(PlatformError::Windows {
+            code: value.code().0,
+            message: value.message(),
+        })
     }
 }
+
+/// Returns the time since the system booted, including time spent suspended.
+pub(crate) fn uptime() -> Duration {
+    Duration::from_millis(unsafe { GetTickCount64() })
+}
@@ -22,7 +22,7 @@ use windows::{
 };
 use windows_core::PWSTR;
 
-use crate::{text::WindowsText, Error, Result};
+use crate::{text::WindowsText, Authentication, AuthenticationMethod, Error, Result};
 
 // Add one to include null byte.
 const MAX_USERNAME_LENGTH: usize = UNLEN as usize + 1;
@@ -34,7 +34,7 @@ type Username = [u16; MAX_USERNAME_LENGTH];
 type Domain = [u16; MAX_DOMAIN_LENGTH];
 type Password = [u16; MAX_PASSWORD_LENGTH];
 
-pub(super) fn authenticate(text: WindowsText) -> Result<()> {
+pub(super) fn authenticate(text: WindowsText) -> Result<Authentication> {
     let (auth_buf, auth_buf_size) = ui_prompt(text)?;
     let ((username, username_size), password) =
         unpack_authentication_buffer(auth_buf, auth_buf_size)?;
@@ -45,7 +45,17 @@ pub(super) fn authenticate(text: WindowsText) -> Result<()> {
     }
 
     let (account_name, domain) = parse_username(username)?;
-    logon_user(account_name, domain, password)
+    logon_user(account_name, domain, password)?;
+
+    let len = username
+        .iter()
+        .position(|c| *c == 0)
+        .unwrap_or(username.len());
+    Ok(Authentication::new(
+        Some(AuthenticationMethod::Credential),
+        None,
+        Some(String::from_utf16_lossy(&username[..len])),
+    ))
 }
 
 fn ui_prompt(text: WindowsText) -> Result<(*mut c_void, u32)> {
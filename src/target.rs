@@ -0,0 +1,292 @@
+use std::fmt;
+
+use crate::{packaging::is_valid_action_id, BiometricStrength, PolicyBuilder};
+
+/// A target that policies can be built for.
+///
+/// Policies are mapped onto each target's native authentication API using
+/// [`PolicyBuilder::mapping`], which can be called on any host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Target {
+    Android,
+    /// iOS, macOS, and other Apple targets except watchOS.
+    Apple,
+    #[doc(alias = "watchos")]
+    WatchOs,
+    Linux,
+    Windows,
+}
+
+impl Target {
+    /// Returns the target being compiled for, or `None` if authentication
+    /// isn't supported on it.
+    pub const fn current() -> Option<Self> {
+        if cfg!(target_os = "android") {
+            Some(Self::Android)
+        } else if cfg!(target_os = "watchos") {
+            Some(Self::WatchOs)
+        } else if cfg!(target_vendor = "apple") {
+            Some(Self::Apple)
+        } else if cfg!(target_os = "linux") {
+            Some(Self::Linux)
+        } else if cfg!(target_os = "windows") {
+            Some(Self::Windows)
+        } else {
+            None
+        }
+    }
+}
+
+/// How a policy is mapped onto a target's native authentication API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyMapping {
+    /// The [`LAPolicy`] that is evaluated.
+    ///
+    /// [`LAPolicy`]: https://developer.apple.com/documentation/localauthentication/lapolicy
+    Apple(ApplePolicy),
+    /// The authenticators the biometric prompt allows.
+    Android(AndroidAuthenticators),
+    /// How the user is authenticated on Linux.
+    Linux(LinuxPolicy),
+    /// Windows Hello is used, which always allows both biometrics and a PIN,
+    /// falling back to the account's password if it isn't set up.
+    Windows,
+}
+
+/// An [`LAPolicy`].
+///
+/// [`LAPolicy`]: https://developer.apple.com/documentation/localauthentication/lapolicy
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApplePolicy {
+    DeviceOwnerAuthentication,
+    DeviceOwnerAuthenticationWithBiometrics,
+    DeviceOwnerAuthenticationWithBiometricsOrWatch,
+    DeviceOwnerAuthenticationWithWatch,
+    DeviceOwnerAuthenticationWithWristDetection,
+}
+
+/// A set of Android [`BiometricManager.Authenticators`] flags, as passed to
+/// `BiometricPrompt.Builder.setAllowedAuthenticators`.
+///
+/// [`BiometricManager.Authenticators`]: https://developer.android.com/reference/android/hardware/biometrics/BiometricManager.Authenticators
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AndroidAuthenticators(i32);
+
+impl AndroidAuthenticators {
+    pub const BIOMETRIC_STRONG: Self = Self(0xf);
+    pub const BIOMETRIC_WEAK: Self = Self(0xff);
+    pub const DEVICE_CREDENTIAL: Self = Self(0x8000);
+
+    /// Returns the flags as passed to Android.
+    pub const fn bits(self) -> i32 {
+        self.0
+    }
+
+    /// Returns whether all flags in `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the union of both sets of flags.
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// How the user is authenticated on Linux.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LinuxPolicy {
+    /// Whether the user's fingerprint is verified using fprintd.
+    pub biometrics: bool,
+    /// Whether the user's password is verified.
+    pub password: bool,
+    /// The polkit action that is checked to verify the password.
+    pub action_id: &'static str,
+    /// The PAM service that verifies the password instead of polkit, if any.
+    pub pam_service: Option<&'static str>,
+    /// Whether both the user's fingerprint and password are verified, in
+    /// which case the password is always verified using PAM.
+    pub require_all: bool,
+}
+
+/// The reason a policy isn't valid for a target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyError {
+    /// Authentication isn't supported on the target.
+    UnsupportedTarget,
+    /// The policy allows no way of authenticating.
+    NoMethod,
+    /// The target requires biometrics to be allowed, i.e. on Android and
+    /// Windows.
+    BiometricsRequired,
+    /// The target requires passwords to be allowed, i.e. on watchOS and
+    /// Windows.
+    PasswordRequired,
+    /// The target can only allow passwords alongside biometrics, i.e. on
+    /// Apple targets.
+    PasswordWithoutBiometrics,
+    /// The target can only allow passwords alongside a watch, i.e. on Apple
+    /// targets.
+    PasswordWithoutWatch,
+    /// The polkit action isn't a valid action ID.
+    InvalidActionId,
+    /// The PAM service is empty or contains a nul byte.
+    InvalidPamService,
+    /// The target can't require every method the policy allows, i.e. on
+    /// targets other than Linux.
+    RequireAllUnsupported,
+    /// Every method is required, but the policy only allows one.
+    RequireAllSingleMethod,
+}
+
+impl PolicyError {
+    /// Returns a description of the error.
+    ///
+    /// This is the error's [`Display`](fmt::Display) output, but is available
+    /// in `const` contexts.
+    pub const fn message(self) -> &'static str {
+        match self {
+            Self::UnsupportedTarget => "authentication isn't supported on this target",
+            Self::NoMethod => "the policy allows neither biometrics nor passwords",
+            Self::BiometricsRequired => "the target requires biometrics to be allowed",
+            Self::PasswordRequired => "the target requires passwords to be allowed",
+            Self::PasswordWithoutBiometrics => {
+                "the target can't allow passwords without biometrics"
+            }
+            Self::PasswordWithoutWatch => "the target can't allow passwords without a watch",
+            Self::InvalidActionId => "the polkit action ID isn't valid",
+            Self::InvalidPamService => "the PAM service isn't valid",
+            Self::RequireAllUnsupported => "the target can't require every method",
+            Self::RequireAllSingleMethod => "the policy requires every method but only allows one",
+        }
+    }
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+pub(crate) const fn is_valid_pam_service(service: &str) -> bool {
+    let bytes = service.as_bytes();
+    if bytes.is_empty() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == 0 {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+pub(crate) const fn map(
+    builder: &PolicyBuilder,
+    target: Target,
+) -> Result<PolicyMapping, PolicyError> {
+    match target {
+        Target::Android => android(builder),
+        Target::Apple => apple(builder),
+        Target::WatchOs => watchos(builder),
+        Target::Linux => linux(builder),
+        Target::Windows => windows(builder),
+    }
+}
+
+const fn android(builder: &PolicyBuilder) -> Result<PolicyMapping, PolicyError> {
+    if builder.require_all {
+        return Err(PolicyError::RequireAllUnsupported);
+    }
+    let biometrics = match builder.biometrics {
+        Some(BiometricStrength::Strong) => AndroidAuthenticators::BIOMETRIC_STRONG,
+        Some(BiometricStrength::Weak) => AndroidAuthenticators::BIOMETRIC_WEAK,
+        None => return Err(PolicyError::BiometricsRequired),
+    };
+    Ok(PolicyMapping::Android(if builder.password {
+        biometrics.union(AndroidAuthenticators::DEVICE_CREDENTIAL)
+    } else {
+        biometrics
+    }))
+}
+
+const fn apple(builder: &PolicyBuilder) -> Result<PolicyMapping, PolicyError> {
+    if builder.require_all {
+        return Err(PolicyError::RequireAllUnsupported);
+    }
+    let policy = match (
+        builder.biometrics.is_some(),
+        builder.password,
+        builder.watch,
+    ) {
+        (true, true, true) => ApplePolicy::DeviceOwnerAuthentication,
+        (true, false, true) => ApplePolicy::DeviceOwnerAuthenticationWithBiometricsOrWatch,
+        (true, false, false) => ApplePolicy::DeviceOwnerAuthenticationWithBiometrics,
+        (false, false, true) => ApplePolicy::DeviceOwnerAuthenticationWithWatch,
+        (false, false, false) => return Err(PolicyError::NoMethod),
+        (false, true, _) => return Err(PolicyError::PasswordWithoutBiometrics),
+        (true, true, false) => return Err(PolicyError::PasswordWithoutWatch),
+    };
+    Ok(PolicyMapping::Apple(policy))
+}
+
+const fn watchos(builder: &PolicyBuilder) -> Result<PolicyMapping, PolicyError> {
+    // TODO: Test watchos
+    if builder.require_all {
+        return Err(PolicyError::RequireAllUnsupported);
+    }
+    if !builder.password {
+        return Err(PolicyError::PasswordRequired);
+    }
+    Ok(PolicyMapping::Apple(if builder.wrist_detection {
+        ApplePolicy::DeviceOwnerAuthenticationWithWristDetection
+    } else {
+        ApplePolicy::DeviceOwnerAuthentication
+    }))
+}
+
+const fn linux(builder: &PolicyBuilder) -> Result<PolicyMapping, PolicyError> {
+    // Fingerprints are verified using fprintd, and passwords using either PAM
+    // or polkit, whose authentication agents authenticate using the user's
+    // password.
+    if builder.biometrics.is_none() && !builder.password {
+        return Err(PolicyError::NoMethod);
+    }
+    if builder.require_all && (builder.biometrics.is_none() || !builder.password) {
+        return Err(PolicyError::RequireAllSingleMethod);
+    }
+    if !is_valid_action_id(builder.action_id) {
+        return Err(PolicyError::InvalidActionId);
+    }
+    if let Some(service) = builder.pam_service {
+        if !is_valid_pam_service(service) {
+            return Err(PolicyError::InvalidPamService);
+        }
+    }
+    Ok(PolicyMapping::Linux(LinuxPolicy {
+        biometrics: builder.biometrics.is_some(),
+        password: builder.password,
+        action_id: builder.action_id,
+        pam_service: builder.pam_service,
+        require_all: builder.require_all,
+    }))
+}
+
+const fn windows(builder: &PolicyBuilder) -> Result<PolicyMapping, PolicyError> {
+    if builder.require_all {
+        return Err(PolicyError::RequireAllUnsupported);
+    }
+    if builder.biometrics.is_none() {
+        Err(PolicyError::BiometricsRequired)
+    } else if !builder.password {
+        Err(PolicyError::PasswordRequired)
+    } else {
+        Ok(PolicyMapping::Windows)
+    }
+}
@@ -1,5 +1,8 @@
 /// The text of the authentication prompt.
-pub struct Text<'a, 'b, 'c, 'd, 'e, 'f> {
+///
+/// See [`TextBuf`] for an owned version, e.g. for text built at runtime.
+#[derive(Clone, Copy, Debug)]
+pub struct Text<'a, 'b, 'c, 'd, 'e, 'f, 'g> {
     /// The text of the authentication prompt on Android.
     pub android: AndroidText<'a, 'b, 'c>,
     /// The description of the authentication prompt on Apple devices.
@@ -8,15 +11,42 @@ pub struct Text<'a, 'b, 'c, 'd, 'e, 'f> {
     pub apple: &'d str,
     /// The description of the authentication prompt on Windows.
     pub windows: WindowsText<'e, 'f>,
+    /// The text of the authentication prompt on Linux.
+    pub linux: LinuxText<'g>,
 }
 
 /// The text of the authentication prompt on Android.
+#[derive(Clone, Copy, Debug)]
 pub struct AndroidText<'a, 'b, 'c> {
     pub title: &'a str,
     pub subtitle: Option<&'b str>,
     pub description: Option<&'c str>,
 }
 
+/// The text of the authentication prompt on Linux.
+///
+/// The message is shown while verifying the user's fingerprint, and before
+/// asking for their password using PAM, by the policy's [`Prompt`] or the
+/// terminal.
+///
+/// The desktop's authentication agent (e.g. GNOME Shell or the KDE polkit
+/// agent) instead shows the `<message>` and `<icon_name>` declared for the
+/// action in its policy file (see [`packaging`]), as polkit only lets root and
+/// the action's owner pass their own.
+///
+/// [`Prompt`]: crate::Prompt
+/// [`packaging`]: crate::packaging
+#[derive(Clone, Copy, Debug)]
+pub struct LinuxText<'a> {
+    /// The message describing why authentication is required.
+    pub message: &'a str,
+}
+
+/// The text of the authentication prompt on Windows.
+///
+/// The title and description are limited to the lengths the credential prompt
+/// supports, so the text can only be constructed using [`WindowsText::new`].
+#[derive(Clone, Copy, Debug)]
 pub struct WindowsText<'a, 'b> {
     #[allow(dead_code)]
     pub(crate) title: &'a str,
@@ -25,23 +55,121 @@ pub struct WindowsText<'a, 'b> {
 }
 
 impl<'a, 'b> WindowsText<'a, 'b> {
-    #[cfg(target_os = "windows")]
+    /// Returns the text, or `None` if the title or description is too long.
+    ///
+    /// The lengths are checked on every target, so text that is valid on one
+    /// target is valid on all of them.
     pub const fn new(title: &'a str, description: &'b str) -> Option<Self> {
-        use windows::Win32::Security::Credentials::{
-            CREDUI_MAX_CAPTION_LENGTH, CREDUI_MAX_MESSAGE_LENGTH,
-        };
-
-        if title.len() <= CREDUI_MAX_CAPTION_LENGTH as usize
-            && description.len() <= CREDUI_MAX_MESSAGE_LENGTH as usize
-        {
+        if is_valid_windows_text(title, description) {
             Some(Self { title, description })
         } else {
             None
         }
     }
+}
 
-    #[cfg(not(target_os = "windows"))]
-    pub const fn new(title: &'a str, description: &'b str) -> Option<Self> {
-        Some(Self { title, description })
+/// The maximum length of the title, i.e. `CREDUI_MAX_CAPTION_LENGTH`.
+const MAX_WINDOWS_TITLE_LENGTH: usize = 128;
+/// The maximum length of the description, i.e. `CREDUI_MAX_MESSAGE_LENGTH`.
+const MAX_WINDOWS_DESCRIPTION_LENGTH: usize = 1024;
+
+const fn is_valid_windows_text(title: &str, description: &str) -> bool {
+    title.len() <= MAX_WINDOWS_TITLE_LENGTH && description.len() <= MAX_WINDOWS_DESCRIPTION_LENGTH
+}
+
+/// An owned version of [`Text`], e.g. for text built at runtime from localized
+/// resources.
+///
+/// The text is passed to [`Context::authenticate`](crate::Context::authenticate)
+/// using [`TextBuf::as_text`].
+#[derive(Clone, Debug)]
+pub struct TextBuf {
+    /// The text of the authentication prompt on Android.
+    pub android: AndroidTextBuf,
+    /// The description of the authentication prompt on Apple devices.
+    ///
+    /// Appears as "$(binary_name) is trying to $(description)".
+    pub apple: String,
+    /// The description of the authentication prompt on Windows.
+    pub windows: WindowsTextBuf,
+    /// The text of the authentication prompt on Linux.
+    pub linux: LinuxTextBuf,
+}
+
+impl TextBuf {
+    /// Borrows the text.
+    pub fn as_text(&self) -> Text<'_, '_, '_, '_, '_, '_, '_> {
+        Text {
+            android: AndroidText {
+                title: &self.android.title,
+                subtitle: self.android.subtitle.as_deref(),
+                description: self.android.description.as_deref(),
+            },
+            apple: &self.apple,
+            windows: WindowsText {
+                title: &self.windows.title,
+                description: &self.windows.description,
+            },
+            linux: LinuxText {
+                message: &self.linux.message,
+            },
+        }
+    }
+}
+
+impl From<Text<'_, '_, '_, '_, '_, '_, '_>> for TextBuf {
+    fn from(text: Text<'_, '_, '_, '_, '_, '_, '_>) -> Self {
+        Self {
+            android: AndroidTextBuf {
+                title: text.android.title.to_owned(),
+                subtitle: text.android.subtitle.map(str::to_owned),
+                description: text.android.description.map(str::to_owned),
+            },
+            apple: text.apple.to_owned(),
+            windows: WindowsTextBuf {
+                title: text.windows.title.to_owned(),
+                description: text.windows.description.to_owned(),
+            },
+            linux: LinuxTextBuf {
+                message: text.linux.message.to_owned(),
+            },
+        }
+    }
+}
+
+/// An owned version of [`AndroidText`].
+#[derive(Clone, Debug)]
+pub struct AndroidTextBuf {
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub description: Option<String>,
+}
+
+/// An owned version of [`LinuxText`].
+#[derive(Clone, Debug)]
+pub struct LinuxTextBuf {
+    /// The message describing why authentication is required.
+    pub message: String,
+}
+
+/// An owned version of [`WindowsText`].
+#[derive(Clone, Debug)]
+pub struct WindowsTextBuf {
+    title: String,
+    description: String,
+}
+
+impl WindowsTextBuf {
+    /// Returns the text, or `None` if the title or description is too long.
+    ///
+    /// See [`WindowsText::new`].
+    pub fn new(title: impl Into<String>, description: impl Into<String>) -> Option<Self> {
+        let title = title.into();
+        let description = description.into();
+        if is_valid_windows_text(&title, &description) {
+            Some(Self { title, description })
+        } else {
+            None
+        }
     }
 }
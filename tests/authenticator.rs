@@ -0,0 +1,216 @@
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+
+use robius_authentication::{
+    AndroidText, AuthEvent, Authentication, AuthenticationMethod, AuthenticationRequest,
+    Authenticator, Availability, AvailabilityStatus, Context, Error, Fallback, LinuxText, Policy,
+    PolicyBuilder, Result, Text, WindowsText,
+};
+
+const TEXT: Text = Text {
+    android: AndroidText {
+        title: "Title",
+        subtitle: None,
+        description: None,
+    },
+    apple: "authenticate",
+    windows: WindowsText::new("Title", "Description").unwrap(),
+    linux: LinuxText {
+        message: "Touch your security key",
+    },
+};
+
+/// A hardware token that accepts a touch as a credential.
+#[derive(Debug, Default)]
+struct Token {
+    state: Mutex<TokenState>,
+    cancelled: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    present: bool,
+    /// Wait for the request to be cancelled instead of being touched.
+    pending: bool,
+    cancelled: bool,
+    requests: Vec<String>,
+}
+
+impl Token {
+    fn present() -> Self {
+        let token = Self::default();
+        token.state.lock().unwrap().present = true;
+        token
+    }
+}
+
+impl Authenticator for Token {
+    fn blocking_authenticate(&self, request: AuthenticationRequest<'_>) -> Result<Authentication> {
+        let mut state = self.state.lock().unwrap();
+        state.requests.push(request.text().linux.message.to_owned());
+        if !state.present || !request.policy().password() {
+            return Err(Error::Unavailable);
+        }
+        request.report(AuthEvent::Help("Touch the key".to_owned()));
+
+        state.cancelled = false;
+        if state.pending {
+            let state = self
+                .cancelled
+                .wait_while(state, |state| !state.cancelled)
+                .unwrap();
+            drop(state);
+            return Err(Error::AppCanceled);
+        }
+        Ok(Authentication::new(
+            Some(AuthenticationMethod::Credential),
+            None,
+            Some("token".to_owned()),
+        ))
+    }
+
+    fn availability(&self, policy: &Policy) -> Result<Availability> {
+        let available = self.state.lock().unwrap().present && policy.password();
+        Ok(Availability {
+            status: if available {
+                AvailabilityStatus::Available
+            } else {
+                AvailabilityStatus::Unavailable
+            },
+            biometry: None,
+        })
+    }
+
+    fn cancel(&self) {
+        self.state.lock().unwrap().cancelled = true;
+        self.cancelled.notify_all();
+    }
+}
+
+/// A token that the test keeps a reference to after passing it to a context.
+#[derive(Debug)]
+struct Shared(Arc<Token>);
+
+impl Authenticator for Shared {
+    fn blocking_authenticate(&self, request: AuthenticationRequest<'_>) -> Result<Authentication> {
+        self.0.blocking_authenticate(request)
+    }
+
+    fn availability(&self, policy: &Policy) -> Result<Availability> {
+        self.0.availability(policy)
+    }
+
+    fn cancel(&self) {
+        self.0.cancel();
+    }
+}
+
+fn policy() -> Policy {
+    PolicyBuilder::new().biometrics(None).build().unwrap()
+}
+
+#[test]
+fn custom_backend() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut context = Context::with_authenticator(Token::present());
+    let reported = events.clone();
+    context.set_event_handler(move |event| reported.lock().unwrap().push(event));
+
+    let authentication = context.blocking_authenticate(TEXT, &policy()).unwrap();
+    assert_eq!(
+        authentication.method,
+        Some(AuthenticationMethod::Credential)
+    );
+    assert_eq!(authentication.account.as_deref(), Some("token"));
+    assert_eq!(
+        *events.lock().unwrap(),
+        [AuthEvent::Help("Touch the key".to_owned())]
+    );
+    assert!(context.availability(&policy()).unwrap().is_available());
+}
+
+#[test]
+fn custom_backend_receives_policy() {
+    let context = Context::with_authenticator(Token::present());
+    let fingerprint = PolicyBuilder::new().password(false).build();
+    // Policies that only allow biometrics can't be built on every target.
+    let Ok(fingerprint) = fingerprint else {
+        return;
+    };
+
+    assert!(matches!(
+        context.blocking_authenticate(TEXT, &fingerprint),
+        Err(Error::Unavailable)
+    ));
+    assert_eq!(
+        context.availability(&fingerprint).unwrap().status,
+        AvailabilityStatus::Unavailable
+    );
+}
+
+#[test]
+fn custom_backend_reuse() {
+    let policy = PolicyBuilder::new()
+        .biometrics(None)
+        .reuse_duration(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    let token = Arc::new(Token::present());
+    let context = Context::with_authenticator(Shared(token.clone()));
+
+    assert!(context.blocking_authenticate(TEXT, &policy).is_ok());
+    assert!(context.blocking_authenticate(TEXT, &policy).is_ok());
+    assert_eq!(token.state.lock().unwrap().requests.len(), 1);
+
+    context.forget_authentications();
+    assert!(context.blocking_authenticate(TEXT, &policy).is_ok());
+    assert_eq!(token.state.lock().unwrap().requests.len(), 2);
+}
+
+#[test]
+fn custom_backend_fallback() {
+    let fallback = Fallback::new().then(policy()).then(policy());
+    let context = Context::with_authenticator(Token::default());
+
+    assert!(matches!(
+        context.blocking_authenticate_with_fallback(TEXT, &fallback),
+        Err(Error::Unavailable)
+    ));
+}
+
+#[test]
+fn custom_backend_timeout() {
+    let token = Token::present();
+    token.state.lock().unwrap().pending = true;
+    let policy = PolicyBuilder::new()
+        .biometrics(None)
+        .timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+
+    assert!(matches!(
+        Context::with_authenticator(token).blocking_authenticate(TEXT, &policy),
+        Err(Error::Timeout)
+    ));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn custom_backend_async() {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context as TaskContext, Poll, Waker},
+    };
+
+    let context = Context::with_authenticator(Token::present());
+    let policy = policy();
+    // The default implementation authenticates before returning.
+    let future = pin!(context.authenticate(TEXT, &policy));
+    let Poll::Ready(result) = future.poll(&mut TaskContext::from_waker(Waker::noop())) else {
+        panic!("future is pending");
+    };
+    assert!(result.is_ok());
+}
@@ -0,0 +1,170 @@
+//! Helpers for running backends against mock services on a private D-Bus bus,
+//! and against test PAM services.
+
+#![allow(dead_code)]
+
+pub mod pam;
+pub mod prompt;
+
+use std::{
+    io::{BufRead, BufReader},
+    os::unix::process::CommandExt,
+    process::{Command, Stdio},
+    sync::{mpsc, Mutex, MutexGuard, OnceLock},
+    thread,
+};
+
+use gio::{glib, prelude::*};
+
+/// Returns the address of a private bus that is used as the system bus for the
+/// rest of the test process.
+///
+/// The process is also detached from its controlling terminal.
+///
+/// Returns `None` if `dbus-daemon` isn't installed, in which case the test
+/// should be skipped.
+pub fn system_bus() -> Option<&'static str> {
+    static ADDRESS: OnceLock<Option<String>> = OnceLock::new();
+
+    ADDRESS
+        .get_or_init(|| {
+            // Without a polkit agent the backend falls back to prompting on the
+            // controlling terminal, which would block tests run from a shell.
+            unsafe { libc::setsid() };
+
+            let address = spawn_bus()?;
+            std::env::set_var("DBUS_SYSTEM_BUS_ADDRESS", &address);
+            Some(address)
+        })
+        .as_deref()
+}
+
+fn spawn_bus() -> Option<String> {
+    let (tx, rx) = mpsc::channel();
+
+    // The daemon is killed when the thread that spawned it exits, so we spawn it
+    // from a thread that lives as long as the test process.
+    thread::spawn(move || {
+        let mut command = Command::new("dbus-daemon");
+        command
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped());
+        unsafe {
+            command.pre_exec(|| {
+                libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM);
+                Ok(())
+            })
+        };
+
+        let Ok(mut daemon) = command.spawn() else {
+            let _ = tx.send(None);
+            return;
+        };
+        let mut address = String::new();
+        let stdout = daemon.stdout.take().expect("failed to get daemon stdout");
+        BufReader::new(stdout)
+            .read_line(&mut address)
+            .expect("failed to read bus address");
+        let _ = tx.send(Some(address.trim().to_owned()));
+
+        loop {
+            thread::park();
+        }
+    });
+
+    rx.recv().expect("failed to spawn bus")
+}
+
+/// Serializes tests that share the mock services.
+pub fn lock() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Handles method calls with the method name, its parameters and the
+/// invocation to reply to.
+pub type Handler = Box<dyn Fn(&str, glib::Variant, gio::DBusMethodInvocation) + Send + Sync>;
+
+/// An object exported by a mock service.
+pub struct Object {
+    pub path: &'static str,
+    pub interface: &'static str,
+    pub properties: Vec<(&'static str, glib::Variant)>,
+    pub handler: Handler,
+}
+
+/// Exports `objects` and claims `name` on the bus at `address`.
+///
+/// Method calls are dispatched to the objects' handlers on a dedicated thread.
+/// The returned connection can be used to emit signals.
+pub fn serve(address: &str, name: &str, objects: Vec<Object>) -> gio::DBusConnection {
+    let (tx, rx) = mpsc::channel();
+    let address = address.to_owned();
+    let name = name.to_owned();
+
+    thread::spawn(move || {
+        let context = glib::MainContext::new();
+        let main_loop = glib::MainLoop::new(Some(&context), false);
+
+        context
+            .with_thread_default(|| {
+                let connection = gio::DBusConnection::for_address_sync(
+                    &address,
+                    gio::DBusConnectionFlags::AUTHENTICATION_CLIENT
+                        | gio::DBusConnectionFlags::MESSAGE_BUS_CONNECTION,
+                    None,
+                    gio::Cancellable::NONE,
+                )
+                .expect("failed to connect to bus");
+
+                for object in objects {
+                    let info = gio::DBusNodeInfo::for_xml(object.interface)
+                        .expect("invalid interface xml")
+                        .interfaces()[0]
+                        .clone();
+                    let handler = object.handler;
+                    let properties = object.properties;
+                    let _id = connection
+                        .register_object(
+                            object.path,
+                            &info,
+                            move |_, _, _, _, method, parameters, invocation| {
+                                handler(method, parameters, invocation)
+                            },
+                            move |_, _, _, _, property| {
+                                properties
+                                    .iter()
+                                    .find(|(name, _)| *name == property)
+                                    .map(|(_, value)| value.clone())
+                                    .expect("unknown property")
+                            },
+                            |_, _, _, _, _, _| false,
+                        )
+                        .expect("failed to register object");
+                }
+
+                connection
+                    .call_sync(
+                        Some("org.freedesktop.DBus"),
+                        "/org/freedesktop/DBus",
+                        "org.freedesktop.DBus",
+                        "RequestName",
+                        Some(&(name.as_str(), 4u32).to_variant()),
+                        None,
+                        gio::DBusCallFlags::NONE,
+                        -1,
+                        gio::Cancellable::NONE,
+                    )
+                    .expect("failed to request name");
+
+                // The connection is closed when the last reference is dropped, so we
+                // keep one alive for as long as the service is running.
+                tx.send(connection.clone()).unwrap();
+                main_loop.run();
+            })
+            .expect("failed to acquire main context");
+    });
+
+    rx.recv().expect("failed to start service")
+}
@@ -0,0 +1,68 @@
+//! Helpers for running the PAM backend against test services.
+
+use std::{fs, os::unix::fs::PermissionsExt, path::PathBuf, process::Command, sync::OnceLock};
+
+/// The password accepted by the `password` service.
+pub const PASSWORD: &str = "correct horse battery staple";
+
+/// Returns the name of the user running the tests.
+pub fn current_user() -> String {
+    let user = Command::new("id").arg("-un").output().unwrap().stdout;
+    String::from_utf8(user).unwrap().trim().to_owned()
+}
+
+/// Returns a directory containing PAM services for the tests.
+///
+/// The services can be used without root by passing their absolute path to
+/// [`PolicyBuilder::pam_service`](robius_authentication::PolicyBuilder::pam_service).
+pub fn services() -> &'static PathBuf {
+    static SERVICES: OnceLock<PathBuf> = OnceLock::new();
+
+    SERVICES.get_or_init(|| {
+        let dir = std::env::temp_dir().join(format!("robius-pam-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // pam_exec passes the password to the script's stdin, followed by a
+        // nul byte. It fails with a system error if the script fails, so the
+        // service skips pam_deny if it succeeds, like Debian's common-auth.
+        let check = dir.join("check-password");
+        fs::write(
+            &check,
+            format!("#!/bin/sh\n[ \"$(tr -d '\\000')\" = \"{PASSWORD}\" ]\n"),
+        )
+        .unwrap();
+        fs::set_permissions(&check, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let user = current_user();
+        let services = [
+            ("permit", "auth required pam_permit.so".to_owned()),
+            ("deny", "auth required pam_deny.so".to_owned()),
+            (
+                "password",
+                format!(
+                    "auth [success=1 default=ignore] pam_exec.so expose_authtok quiet {}\n\
+                     auth requisite pam_deny.so\n\
+                     auth required pam_permit.so",
+                    check.display()
+                ),
+            ),
+            (
+                "echo",
+                "auth optional pam_echo.so Hello from PAM\nauth required pam_permit.so".to_owned(),
+            ),
+            (
+                "current-user",
+                format!("auth required pam_succeed_if.so quiet user = {user}"),
+            ),
+        ];
+        for (name, auth) in services {
+            fs::write(
+                dir.join(name),
+                format!("{auth}\naccount required pam_permit.so\n"),
+            )
+            .unwrap();
+        }
+
+        dir
+    })
+}
@@ -0,0 +1,48 @@
+//! A prompt for testing backends that ask for the user's password themselves.
+
+use std::sync::Mutex;
+
+use robius_authentication::{Error, Prompt, Result};
+
+/// A prompt that answers with a fixed password and records what it was shown.
+pub struct TestPrompt {
+    /// The password to answer with, or `None` to cancel.
+    password: Option<&'static str>,
+    shown: Mutex<Vec<String>>,
+}
+
+impl TestPrompt {
+    pub fn new(password: Option<&'static str>) -> &'static Self {
+        Box::leak(Box::new(Self {
+            password,
+            shown: Mutex::new(Vec::new()),
+        }))
+    }
+
+    pub fn shown(&self) -> Vec<String> {
+        self.shown.lock().unwrap().clone()
+    }
+}
+
+impl Prompt for TestPrompt {
+    fn secret(&self, message: &str) -> Result<String> {
+        self.shown
+            .lock()
+            .unwrap()
+            .push(format!("secret: {message}"));
+        self.password.map(str::to_owned).ok_or(Error::UserCanceled)
+    }
+
+    fn text(&self, message: &str) -> Result<String> {
+        self.shown.lock().unwrap().push(format!("text: {message}"));
+        Ok(String::new())
+    }
+
+    fn info(&self, message: &str) {
+        self.shown.lock().unwrap().push(format!("info: {message}"));
+    }
+
+    fn error(&self, message: &str) {
+        self.shown.lock().unwrap().push(format!("error: {message}"));
+    }
+}
@@ -0,0 +1,86 @@
+use std::error::Error as _;
+
+use robius_authentication::{Error, PlatformError};
+
+#[test]
+fn display() {
+    assert_eq!(
+        Error::NotEnrolled.to_string(),
+        "the user has no enrolled biometric identities"
+    );
+    assert_eq!(
+        Error::UserCanceled.to_string(),
+        "the user canceled authentication"
+    );
+    // Not only biometrics can be disabled, e.g. polkit can forbid an action.
+    assert_eq!(
+        Error::DisabledByPolicy.to_string(),
+        "authentication is disabled by policy"
+    );
+
+    let error = Error::Platform(PlatformError::Pam {
+        code: 4,
+        message: "System error".to_owned(),
+    });
+    assert_eq!(
+        error.to_string(),
+        "system error: System error (PAM status 4)"
+    );
+}
+
+#[test]
+fn platform() {
+    let platform = PlatformError::Windows {
+        code: 0x8007_0005_u32 as i32,
+        message: "Access is denied.".to_owned(),
+    };
+    let error = Error::Platform(platform.clone());
+
+    assert_eq!(error.platform(), Some(&platform));
+    assert_eq!(
+        platform.to_string(),
+        "Access is denied. (HRESULT 0x80070005)"
+    );
+    assert_eq!(
+        error.source().map(ToString::to_string),
+        Some(platform.to_string())
+    );
+
+    assert_eq!(Error::Unknown.platform(), None);
+    // Errors mapped to other variants don't keep the system's code.
+    assert_eq!(Error::UserCanceled.platform(), None);
+    assert!(Error::Unknown.source().is_none());
+    assert_eq!(
+        PlatformError::LocalAuthentication {
+            code: -1000,
+            message: "Biometry is locked out.".to_owned()
+        }
+        .to_string(),
+        "Biometry is locked out. (LAError -1000)"
+    );
+}
+
+#[test]
+fn classification() {
+    assert!(Error::Authentication.is_retryable());
+    assert!(Error::Busy.is_retryable());
+    assert!(!Error::Exhausted.is_retryable());
+    assert!(!Error::UserCanceled.is_retryable());
+
+    assert!(Error::UserCanceled.is_user_cancellation());
+    assert!(!Error::AppCanceled.is_user_cancellation());
+    assert!(!Error::SystemCanceled.is_user_cancellation());
+
+    assert!(Error::NotEnrolled.is_configuration_problem());
+    assert!(Error::PasscodeNotSet.is_configuration_problem());
+    assert!(!Error::Authentication.is_configuration_problem());
+}
+
+#[test]
+fn boxed() {
+    fn fails() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err(Error::Timeout)?
+    }
+
+    assert_eq!(fails().unwrap_err().to_string(), "authentication timed out");
+}
@@ -0,0 +1,888 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::sync::{Arc, Mutex, OnceLock};
+
+use common::{
+    pam::{current_user, services, PASSWORD},
+    prompt::TestPrompt,
+};
+use gio::{glib, prelude::*};
+use robius_authentication::{
+    linux, policy, AndroidText, AuthEvent, Authentication, AuthenticationMethod,
+    AvailabilityStatus, BiometricStrength, BiometryKind, Context, Error, Fallback,
+    FallbackAuthentication, LinuxText, PlatformError, Policy, PolicyBuilder, Text, WindowsText,
+};
+
+const MANAGER_INTERFACE: &str = r#"
+<node>
+  <interface name="net.reactivated.Fprint.Manager">
+    <method name="GetDevices">
+      <arg type="ao" name="devices" direction="out"/>
+    </method>
+    <method name="GetDefaultDevice">
+      <arg type="o" name="device" direction="out"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+const DEVICE_INTERFACE: &str = r#"
+<node>
+  <interface name="net.reactivated.Fprint.Device">
+    <method name="Claim">
+      <arg type="s" name="username" direction="in"/>
+    </method>
+    <method name="Release"/>
+    <method name="VerifyStart">
+      <arg type="s" name="finger_name" direction="in"/>
+    </method>
+    <method name="VerifyStop"/>
+    <method name="ListEnrolledFingers">
+      <arg type="s" name="username" direction="in"/>
+      <arg type="as" name="enrolled_fingers" direction="out"/>
+    </method>
+    <signal name="VerifyStatus">
+      <arg type="s" name="result"/>
+      <arg type="b" name="done"/>
+    </signal>
+  </interface>
+</node>
+"#;
+
+const DEVICE_PATH: &str = "/net/reactivated/Fprint/Device/0";
+
+const FINGERPRINT: Policy = policy! {
+    biometrics: Some(BiometricStrength::Strong),
+    password: false,
+};
+
+const FINGERPRINT_OR_PASSWORD: Policy = policy! {
+    biometrics: Some(BiometricStrength::Strong),
+    password: true,
+};
+
+const TEXT: Text = Text {
+    android: AndroidText {
+        title: "Title",
+        subtitle: None,
+        description: None,
+    },
+    apple: "authenticate",
+    windows: WindowsText::new("Title", "Description").unwrap(),
+    linux: LinuxText {
+        message: "Unlock the test vault",
+    },
+};
+
+/// The behaviour of the mock fprintd for the next verification.
+#[derive(Clone, Copy, Default)]
+struct Script {
+    /// A method that fails, and the D-Bus error it fails with.
+    error: Option<(&'static str, &'static str)>,
+    /// The `VerifyStatus` signals emitted once the verification starts.
+    statuses: &'static [(&'static str, bool)],
+    /// The fingers the user has enrolled.
+    enrolled: &'static [&'static str],
+}
+
+#[derive(Default)]
+struct Fprintd {
+    script: Script,
+    /// The methods called on the mock, and their string argument if any.
+    calls: Vec<(String, Option<String>)>,
+}
+
+static FPRINTD: OnceLock<Mutex<Fprintd>> = OnceLock::new();
+
+/// Starts the mock fprintd, if it isn't running, and sets the script for the
+/// next verification.
+///
+/// Returns `None` if the test should be skipped.
+fn fprintd(script: Script) -> Option<&'static Mutex<Fprintd>> {
+    let bus = common::system_bus()?;
+    let fprintd = FPRINTD.get_or_init(|| {
+        common::serve(
+            bus,
+            "net.reactivated.Fprint",
+            vec![
+                common::Object {
+                    path: "/net/reactivated/Fprint/Manager",
+                    interface: MANAGER_INTERFACE,
+                    properties: Vec::new(),
+                    handler: Box::new(handle),
+                },
+                common::Object {
+                    path: DEVICE_PATH,
+                    interface: DEVICE_INTERFACE,
+                    properties: Vec::new(),
+                    handler: Box::new(handle),
+                },
+            ],
+        );
+        Mutex::default()
+    });
+
+    let mut state = fprintd.lock().unwrap();
+    state.script = script;
+    state.calls.clear();
+    drop(state);
+
+    Some(fprintd)
+}
+
+fn handle(method: &str, parameters: glib::Variant, invocation: gio::DBusMethodInvocation) {
+    let mut state = FPRINTD.get().unwrap().lock().unwrap();
+    let argument = parameters.get::<(String,)>().map(|(argument,)| argument);
+    state.calls.push((method.to_owned(), argument));
+
+    if let Some((_, name)) = state.script.error.filter(|(failing, _)| *failing == method) {
+        invocation.return_dbus_error(name, "mock error");
+        return;
+    }
+
+    match method {
+        "GetDefaultDevice" => {
+            let device = glib::variant::ObjectPath::try_from(DEVICE_PATH).unwrap();
+            invocation.return_value(Some(&(device,).to_variant()));
+        }
+        // Like fprintd, an empty list is reported as an error.
+        "ListEnrolledFingers" if state.script.enrolled.is_empty() => {
+            invocation.return_dbus_error(
+                "net.reactivated.Fprint.Error.NoEnrolledPrints",
+                "mock error",
+            );
+        }
+        "ListEnrolledFingers" => {
+            invocation.return_value(Some(&(state.script.enrolled.to_vec(),).to_variant()));
+        }
+        "VerifyStart" => {
+            let connection = invocation.connection();
+            invocation.return_value(None);
+            for (result, done) in state.script.statuses {
+                connection
+                    .emit_signal(
+                        None,
+                        DEVICE_PATH,
+                        "net.reactivated.Fprint.Device",
+                        "VerifyStatus",
+                        Some(&(*result, *done).to_variant()),
+                    )
+                    .expect("failed to emit signal");
+            }
+        }
+        _ => invocation.return_value(None),
+    }
+}
+
+fn authenticate(
+    script: Script,
+    policy: &Policy,
+) -> Option<(Result<Authentication, Error>, Vec<String>)> {
+    let _lock = common::lock();
+    let fprintd = fprintd(script)?;
+
+    let result = Context::new(()).blocking_authenticate(TEXT, policy);
+    let calls = fprintd
+        .lock()
+        .unwrap()
+        .calls
+        .drain(..)
+        .map(|(method, _)| method)
+        .collect();
+
+    Some((result, calls))
+}
+
+#[test]
+fn verify_match() {
+    let script = Script {
+        statuses: &[("verify-match", true)],
+        ..Script::default()
+    };
+    let Some((result, calls)) = authenticate(script, &FINGERPRINT) else {
+        return;
+    };
+
+    let authentication = result.unwrap();
+    assert_eq!(authentication.method, Some(AuthenticationMethod::Biometric));
+    assert_eq!(authentication.account, Some(current_user()));
+    assert_eq!(
+        calls,
+        [
+            "GetDefaultDevice",
+            "Claim",
+            "VerifyStart",
+            "VerifyStop",
+            "Release"
+        ]
+    );
+}
+
+#[test]
+fn claims_for_current_user_and_any_finger() {
+    let script = Script {
+        statuses: &[("verify-match", true)],
+        ..Script::default()
+    };
+    let _lock = common::lock();
+    let Some(fprintd) = fprintd(script) else {
+        return;
+    };
+
+    Context::new(())
+        .blocking_authenticate(TEXT, &FINGERPRINT)
+        .unwrap();
+
+    let calls = &fprintd.lock().unwrap().calls;
+    assert!(calls.contains(&("Claim".to_owned(), Some(String::new()))));
+    assert!(calls.contains(&("VerifyStart".to_owned(), Some("any".to_owned()))));
+}
+
+#[test]
+fn no_match() {
+    let script = Script {
+        statuses: &[("verify-no-match", true)],
+        ..Script::default()
+    };
+    let Some((result, calls)) = authenticate(script, &FINGERPRINT) else {
+        return;
+    };
+
+    assert!(matches!(result, Err(Error::Authentication)));
+    assert_eq!(calls.last().map(String::as_str), Some("Release"));
+}
+
+#[test]
+fn retry_scan() {
+    let script = Script {
+        statuses: &[
+            ("verify-retry-scan", false),
+            ("verify-finger-not-centered", false),
+            ("verify-match", true),
+        ],
+        ..Script::default()
+    };
+    let Some((result, _)) = authenticate(script, &FINGERPRINT) else {
+        return;
+    };
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn retries_are_reported() {
+    let script = Script {
+        statuses: &[
+            ("verify-retry-scan", false),
+            ("verify-swipe-too-short", false),
+            ("verify-no-match", false),
+            ("verify-match", true),
+        ],
+        ..Script::default()
+    };
+    let _lock = common::lock();
+    if fprintd(script).is_none() {
+        return;
+    }
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut context = Context::new(());
+    context.set_event_handler({
+        let events = events.clone();
+        move |event| events.lock().unwrap().push(event)
+    });
+
+    assert!(context.blocking_authenticate(TEXT, &FINGERPRINT).is_ok());
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            AuthEvent::RetryScan,
+            AuthEvent::SwipeTooShort,
+            AuthEvent::AttemptFailed
+        ]
+    );
+}
+
+#[test]
+fn disconnected() {
+    let script = Script {
+        statuses: &[("verify-disconnected", true)],
+        ..Script::default()
+    };
+    let Some((result, _)) = authenticate(script, &FINGERPRINT) else {
+        return;
+    };
+
+    assert!(matches!(result, Err(Error::BiometryDisconnected)));
+}
+
+#[test]
+fn no_device() {
+    let script = Script {
+        error: Some((
+            "GetDefaultDevice",
+            "net.reactivated.Fprint.Error.NoSuchDevice",
+        )),
+        ..Script::default()
+    };
+    let Some((result, calls)) = authenticate(script, &FINGERPRINT) else {
+        return;
+    };
+
+    assert!(matches!(result, Err(Error::Unavailable)));
+    assert_eq!(calls, ["GetDefaultDevice"]);
+}
+
+#[test]
+fn not_enrolled() {
+    let script = Script {
+        error: Some((
+            "VerifyStart",
+            "net.reactivated.Fprint.Error.NoEnrolledPrints",
+        )),
+        ..Script::default()
+    };
+    let Some((result, calls)) = authenticate(script, &FINGERPRINT) else {
+        return;
+    };
+
+    assert!(matches!(result, Err(Error::NotEnrolled)));
+    assert_eq!(calls.last().map(String::as_str), Some("Release"));
+}
+
+#[test]
+fn busy() {
+    let script = Script {
+        error: Some(("Claim", "net.reactivated.Fprint.Error.AlreadyInUse")),
+        ..Script::default()
+    };
+    let Some((result, _)) = authenticate(script, &FINGERPRINT) else {
+        return;
+    };
+
+    assert!(matches!(result, Err(Error::Busy)));
+}
+
+#[test]
+fn internal_error() {
+    let script = Script {
+        error: Some(("Claim", "net.reactivated.Fprint.Error.Internal")),
+        ..Script::default()
+    };
+    let Some((result, _)) = authenticate(script, &FINGERPRINT) else {
+        return;
+    };
+
+    let Err(Error::Platform(PlatformError::GLib {
+        domain, message, ..
+    })) = result
+    else {
+        panic!("unexpected result: {result:?}");
+    };
+    assert_eq!(domain, "g-io-error-quark");
+    assert!(message.contains("net.reactivated.Fprint.Error.Internal"));
+}
+
+/// Returns a policy that allows the user's fingerprint or their password,
+/// which is verified using the `password` PAM service.
+fn fingerprint_or_password(prompt: &'static TestPrompt) -> Policy {
+    let service = services().join("password").to_str().unwrap().to_owned();
+
+    PolicyBuilder::new()
+        .prompt(prompt)
+        .pam_service(Box::leak(service.into_boxed_str()))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn falls_back_to_password() {
+    let script = Script {
+        error: Some((
+            "VerifyStart",
+            "net.reactivated.Fprint.Error.NoEnrolledPrints",
+        )),
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let Some((result, _)) = authenticate(script, &fingerprint_or_password(prompt)) else {
+        return;
+    };
+
+    assert_eq!(
+        result.unwrap().method,
+        Some(AuthenticationMethod::Credential)
+    );
+    // The reader wasn't ready, so the user wasn't asked to scan their finger.
+    assert_eq!(
+        prompt.shown(),
+        [
+            "info: Unlock the test vault".to_owned(),
+            "secret: Password: ".to_owned()
+        ]
+    );
+}
+
+#[test]
+fn shows_message_while_scanning() {
+    let script = Script {
+        statuses: &[("verify-match", true)],
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let Some((result, _)) = authenticate(script, &fingerprint_or_password(prompt)) else {
+        return;
+    };
+
+    assert_eq!(
+        result.unwrap().method,
+        Some(AuthenticationMethod::Biometric)
+    );
+    assert_eq!(
+        prompt.shown(),
+        [
+            "info: Unlock the test vault".to_owned(),
+            "info: Place your finger on the fingerprint reader".to_owned(),
+        ]
+    );
+}
+
+#[test]
+fn falls_back_after_no_match() {
+    let script = Script {
+        statuses: &[("verify-no-match", true)],
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let Some((result, calls)) = authenticate(script, &fingerprint_or_password(prompt)) else {
+        return;
+    };
+
+    assert_eq!(
+        result.unwrap().method,
+        Some(AuthenticationMethod::Credential)
+    );
+    assert_eq!(calls.last().map(String::as_str), Some("Release"));
+    assert_eq!(
+        prompt.shown().last().map(String::as_str),
+        Some("secret: Password: ")
+    );
+}
+
+#[test]
+fn no_fallback_after_no_match_without_password() {
+    let script = Script {
+        statuses: &[("verify-no-match", true)],
+        ..Script::default()
+    };
+    let Some((result, _)) = authenticate(script, &FINGERPRINT) else {
+        return;
+    };
+
+    assert!(matches!(result, Err(Error::Authentication)));
+}
+
+/// Returns a fallback that tries the user's fingerprint, then their password
+/// using polkit, then a PIN using the `password` PAM service.
+fn fingerprint_password_pin(prompt: &'static TestPrompt) -> Fallback {
+    let service = services().join("password").to_str().unwrap().to_owned();
+
+    Fallback::new()
+        .then(FINGERPRINT)
+        .then(PolicyBuilder::new().biometrics(None).build().unwrap())
+        .then(
+            PolicyBuilder::new()
+                .biometrics(None)
+                .prompt(prompt)
+                .pam_service(Box::leak(service.into_boxed_str()))
+                .build()
+                .unwrap(),
+        )
+}
+
+fn authenticate_with_fallback(
+    script: Script,
+    fallback: &Fallback,
+) -> Option<Result<FallbackAuthentication, Error>> {
+    let _lock = common::lock();
+    fprintd(script)?;
+
+    Some(Context::new(()).blocking_authenticate_with_fallback(TEXT, fallback))
+}
+
+#[test]
+fn fallback_first_step() {
+    let script = Script {
+        statuses: &[("verify-match", true)],
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let Some(result) = authenticate_with_fallback(script, &fingerprint_password_pin(prompt)) else {
+        return;
+    };
+
+    let result = result.unwrap();
+    assert_eq!(result.step, 0);
+    assert_eq!(
+        result.authentication.method,
+        Some(AuthenticationMethod::Biometric)
+    );
+    assert!(prompt.shown().is_empty());
+}
+
+#[test]
+fn fallback_skips_unavailable_steps() {
+    let script = Script {
+        error: Some((
+            "VerifyStart",
+            "net.reactivated.Fprint.Error.NoEnrolledPrints",
+        )),
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let Some(result) = authenticate_with_fallback(script, &fingerprint_password_pin(prompt)) else {
+        return;
+    };
+
+    // polkit isn't running on the private bus, so the PIN is used.
+    let result = result.unwrap();
+    assert_eq!(result.step, 2);
+    assert_eq!(
+        result.authentication.method,
+        Some(AuthenticationMethod::Credential)
+    );
+    assert_eq!(
+        result.authentication.account.as_deref(),
+        Some(current_user().as_str())
+    );
+}
+
+#[test]
+fn fallback_stops_after_no_match() {
+    let script = Script {
+        statuses: &[("verify-no-match", true)],
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let Some(result) = authenticate_with_fallback(script, &fingerprint_password_pin(prompt)) else {
+        return;
+    };
+
+    assert!(matches!(result, Err(Error::Authentication)));
+    assert!(prompt.shown().is_empty());
+}
+
+#[test]
+fn fallback_returns_last_error() {
+    let script = Script {
+        error: Some((
+            "GetDefaultDevice",
+            "net.reactivated.Fprint.Error.NoSuchDevice",
+        )),
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(None);
+    let Some(result) = authenticate_with_fallback(script, &fingerprint_password_pin(prompt)) else {
+        return;
+    };
+
+    // The prompt cancels when asked for the PIN.
+    assert!(matches!(result, Err(Error::UserCanceled)));
+
+    let fallback = Fallback::new().then(FINGERPRINT);
+    let Some(result) = authenticate_with_fallback(script, &fallback) else {
+        return;
+    };
+    assert!(matches!(result, Err(Error::Unavailable)));
+
+    assert!(matches!(
+        Context::new(()).blocking_authenticate_with_fallback(TEXT, &Fallback::new()),
+        Err(Error::Unavailable)
+    ));
+}
+
+/// Returns a policy that requires the user's fingerprint followed by their
+/// password, which is verified using the `password` PAM service.
+fn fingerprint_and_password(prompt: &'static TestPrompt) -> Policy {
+    let service = services().join("password").to_str().unwrap().to_owned();
+
+    PolicyBuilder::new()
+        .require_all(true)
+        .prompt(prompt)
+        .pam_service(Box::leak(service.into_boxed_str()))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn require_all() {
+    let script = Script {
+        statuses: &[("verify-match", true)],
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let Some((result, calls)) = authenticate(script, &fingerprint_and_password(prompt)) else {
+        return;
+    };
+
+    let authentication = result.unwrap();
+    assert_eq!(
+        authentication.factors,
+        [
+            AuthenticationMethod::Biometric,
+            AuthenticationMethod::Credential
+        ]
+    );
+    assert_eq!(
+        authentication.method,
+        Some(AuthenticationMethod::Credential)
+    );
+    assert_eq!(authentication.account, Some(current_user()));
+    assert_eq!(calls.last().map(String::as_str), Some("Release"));
+    assert_eq!(
+        prompt.shown(),
+        [
+            "info: Unlock the test vault".to_owned(),
+            "info: Place your finger on the fingerprint reader".to_owned(),
+            "info: Unlock the test vault".to_owned(),
+            "secret: Password: ".to_owned()
+        ]
+    );
+}
+
+#[test]
+fn require_all_stops_after_no_match() {
+    let script = Script {
+        statuses: &[("verify-no-match", true)],
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let Some((result, _)) = authenticate(script, &fingerprint_and_password(prompt)) else {
+        return;
+    };
+
+    // The password isn't asked for.
+    assert!(matches!(result, Err(Error::Authentication)));
+    assert_eq!(
+        prompt.shown(),
+        [
+            "info: Unlock the test vault".to_owned(),
+            "info: Place your finger on the fingerprint reader".to_owned(),
+        ]
+    );
+}
+
+#[test]
+fn require_all_without_fingerprint() {
+    let script = Script {
+        error: Some((
+            "VerifyStart",
+            "net.reactivated.Fprint.Error.NoEnrolledPrints",
+        )),
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let policy = fingerprint_and_password(prompt);
+    let Some((result, _)) = authenticate(script, &policy) else {
+        return;
+    };
+
+    // The password alone isn't enough.
+    assert!(matches!(result, Err(Error::NotEnrolled)));
+    assert!(prompt.shown().is_empty());
+
+    let _lock = common::lock();
+    fprintd(script);
+    let availability = Context::new(()).availability(&policy).unwrap();
+    assert_eq!(availability.status, AvailabilityStatus::NotEnrolled);
+}
+
+#[test]
+fn require_all_incorrect_password() {
+    let script = Script {
+        statuses: &[("verify-match", true)],
+        ..Script::default()
+    };
+    let prompt = TestPrompt::new(Some("Tr0ub4dor&3"));
+    let Some((result, _)) = authenticate(script, &fingerprint_and_password(prompt)) else {
+        return;
+    };
+
+    assert!(matches!(result, Err(Error::Authentication)));
+}
+
+#[test]
+fn handle_stops_verification() {
+    use std::time::Duration;
+
+    let _lock = common::lock();
+    let Some(fprintd) = fprintd(Script::default()) else {
+        return;
+    };
+
+    let context = Context::new(());
+    let handle = context.handle();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(200));
+        handle.cancel();
+    });
+    let result = context.blocking_authenticate(TEXT, &FINGERPRINT);
+    canceller.join().unwrap();
+
+    assert!(matches!(result, Err(Error::AppCanceled)));
+    let calls = &fprintd.lock().unwrap().calls;
+    assert_eq!(
+        calls.last().map(|(method, _)| method.as_str()),
+        Some("Release")
+    );
+}
+
+#[test]
+fn fprintd_backend() {
+    let script = Script {
+        statuses: &[("verify-match", true)],
+        ..Script::default()
+    };
+    let _lock = common::lock();
+    if fprintd(script).is_none() {
+        return;
+    }
+
+    let context = Context::with_authenticator(linux::Fprintd::new());
+    let authentication = context
+        .blocking_authenticate(TEXT, &FINGERPRINT_OR_PASSWORD)
+        .unwrap();
+    assert_eq!(authentication.method, Some(AuthenticationMethod::Biometric));
+}
+
+#[test]
+fn fprintd_backend_doesnt_fall_back() {
+    let script = Script {
+        error: Some((
+            "GetDefaultDevice",
+            "net.reactivated.Fprint.Error.NoSuchDevice",
+        )),
+        ..Script::default()
+    };
+    let _lock = common::lock();
+    let Some(fprintd) = fprintd(script) else {
+        return;
+    };
+
+    let context = Context::with_authenticator(linux::Fprintd::new());
+    assert!(matches!(
+        context.blocking_authenticate(TEXT, &FINGERPRINT_OR_PASSWORD),
+        Err(Error::Unavailable)
+    ));
+
+    // The reader isn't used if the policy doesn't allow biometrics.
+    fprintd.lock().unwrap().calls.clear();
+    let password = PolicyBuilder::new().biometrics(None).build().unwrap();
+    assert!(matches!(
+        context.blocking_authenticate(TEXT, &password),
+        Err(Error::Unavailable)
+    ));
+    assert!(fprintd.lock().unwrap().calls.is_empty());
+}
+
+#[test]
+fn available() {
+    let script = Script {
+        enrolled: &["right-index-finger"],
+        ..Script::default()
+    };
+    let _lock = common::lock();
+    let Some(fprintd) = fprintd(script) else {
+        return;
+    };
+
+    let availability = Context::new(()).availability(&FINGERPRINT).unwrap();
+
+    assert_eq!(availability.status, AvailabilityStatus::Available);
+    assert_eq!(availability.biometry, Some(BiometryKind::Fingerprint));
+    // The device isn't claimed, so checking doesn't interrupt other users.
+    assert_eq!(
+        fprintd.lock().unwrap().calls,
+        [
+            ("GetDefaultDevice".to_owned(), None),
+            ("ListEnrolledFingers".to_owned(), Some(String::new()))
+        ]
+    );
+}
+
+#[test]
+fn available_not_enrolled() {
+    let _lock = common::lock();
+    if fprintd(Script::default()).is_none() {
+        return;
+    }
+
+    let availability = Context::new(()).availability(&FINGERPRINT).unwrap();
+
+    assert_eq!(availability.status, AvailabilityStatus::NotEnrolled);
+    assert_eq!(availability.biometry, Some(BiometryKind::Fingerprint));
+}
+
+#[test]
+fn available_no_device() {
+    let script = Script {
+        error: Some((
+            "GetDefaultDevice",
+            "net.reactivated.Fprint.Error.NoSuchDevice",
+        )),
+        ..Script::default()
+    };
+    let _lock = common::lock();
+    if fprintd(script).is_none() {
+        return;
+    }
+
+    let availability = Context::new(()).availability(&FINGERPRINT).unwrap();
+
+    assert_eq!(availability.status, AvailabilityStatus::Unavailable);
+    assert_eq!(availability.biometry, None);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn dropping_future_stops_verification() {
+    use std::time::{Duration, Instant};
+
+    let _lock = common::lock();
+    let Some(fprintd) = fprintd(Script::default()) else {
+        return;
+    };
+
+    let context = Context::new(());
+    let result = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(async {
+            tokio::time::timeout(
+                Duration::from_millis(200),
+                context.authenticate(TEXT, &FINGERPRINT),
+            )
+            .await
+        });
+    assert!(result.is_err(), "verification wasn't pending");
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let state = fprintd.lock().unwrap();
+        if state
+            .calls
+            .last()
+            .is_some_and(|(method, _)| method == "Release")
+        {
+            assert!(state.calls.iter().any(|(method, _)| method == "VerifyStop"));
+            break;
+        }
+        drop(state);
+
+        assert!(Instant::now() < deadline, "device wasn't released");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
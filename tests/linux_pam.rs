@@ -0,0 +1,331 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::{sync::OnceLock, thread, time::Duration};
+
+use common::{
+    pam::{current_user, services, PASSWORD},
+    prompt::TestPrompt,
+};
+use robius_authentication::{
+    linux, AndroidText, Authentication, AuthenticationHandle, AuthenticationMethod,
+    BiometricStrength, Context, Error, LinuxText, Policy, PolicyBuilder, PolicyError, Prompt,
+    Result, Text, TextBuf, WindowsText,
+};
+
+const TEXT: Text = Text {
+    android: AndroidText {
+        title: "Title",
+        subtitle: None,
+        description: None,
+    },
+    apple: "authenticate",
+    windows: WindowsText::new("Title", "Description").unwrap(),
+    linux: LinuxText {
+        message: "Unlock the test vault",
+    },
+};
+
+fn builder(service: &str, prompt: &'static TestPrompt) -> PolicyBuilder {
+    let service = services().join(service).to_str().unwrap().to_owned();
+
+    PolicyBuilder::new()
+        .biometrics(None)
+        .prompt(prompt)
+        .pam_service(Box::leak(service.into_boxed_str()))
+}
+
+fn policy(service: &str, prompt: &'static TestPrompt) -> Policy {
+    builder(service, prompt).build().unwrap()
+}
+
+/// Returns a policy whose authentications are reused for `reuse_duration`.
+fn reusable(service: &str, prompt: &'static TestPrompt, reuse_duration: Duration) -> Policy {
+    builder(service, prompt)
+        .reuse_duration(reuse_duration)
+        .build()
+        .unwrap()
+}
+
+fn authenticate(service: &str, prompt: &'static TestPrompt) -> Result<Authentication> {
+    Context::new(()).blocking_authenticate(TEXT, &policy(service, prompt))
+}
+
+#[test]
+fn permit() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+
+    assert!(authenticate("permit", prompt).is_ok());
+    assert_eq!(prompt.shown(), ["info: Unlock the test vault"]);
+}
+
+#[test]
+fn deny() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+
+    assert!(matches!(
+        authenticate("deny", prompt),
+        Err(Error::Authentication)
+    ));
+}
+
+#[test]
+fn correct_password() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+
+    let authentication = authenticate("password", prompt).unwrap();
+    assert_eq!(
+        authentication.method,
+        Some(AuthenticationMethod::Credential)
+    );
+    assert_eq!(authentication.account, Some(current_user()));
+    let shown = prompt.shown();
+    assert_eq!(shown.len(), 2);
+    assert!(shown[1].starts_with("secret: "));
+}
+
+#[test]
+fn incorrect_password() {
+    let prompt = TestPrompt::new(Some("hunter2"));
+
+    assert!(matches!(
+        authenticate("password", prompt),
+        Err(Error::Authentication)
+    ));
+}
+
+#[test]
+fn canceled() {
+    let prompt = TestPrompt::new(None);
+
+    assert!(matches!(
+        authenticate("password", prompt),
+        Err(Error::UserCanceled)
+    ));
+}
+
+#[test]
+fn messages_are_shown() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+
+    assert!(authenticate("echo", prompt).is_ok());
+    assert_eq!(
+        prompt.shown(),
+        ["info: Unlock the test vault", "info: Hello from PAM"]
+    );
+}
+
+#[test]
+fn authenticates_current_user() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+
+    assert!(authenticate("current-user", prompt).is_ok());
+}
+
+#[test]
+fn pam_backend() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let context = Context::with_authenticator(linux::Pam::new());
+
+    let authentication = context
+        .blocking_authenticate(TEXT, &policy("password", prompt))
+        .unwrap();
+    assert_eq!(
+        authentication.method,
+        Some(AuthenticationMethod::Credential)
+    );
+    assert!(context
+        .availability(&policy("password", prompt))
+        .unwrap()
+        .is_available());
+}
+
+#[test]
+fn pam_backend_requires_password() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let policy = builder("password", prompt)
+        .biometrics(Some(BiometricStrength::Strong))
+        .password(false)
+        .build()
+        .unwrap();
+
+    assert!(matches!(
+        Context::with_authenticator(linux::Pam::new()).blocking_authenticate(TEXT, &policy),
+        Err(Error::Unavailable)
+    ));
+    assert!(prompt.shown().is_empty());
+}
+
+#[test]
+fn handle_cancels_before_next_message() {
+    /// A prompt that cancels the request while answering.
+    struct CancellingPrompt(OnceLock<AuthenticationHandle>);
+
+    impl Prompt for CancellingPrompt {
+        fn secret(&self, _: &str) -> Result<String> {
+            self.0.get().unwrap().cancel();
+            Ok(PASSWORD.to_owned())
+        }
+
+        fn text(&self, _: &str) -> Result<String> {
+            Ok(String::new())
+        }
+
+        fn info(&self, _: &str) {}
+
+        fn error(&self, _: &str) {}
+    }
+
+    let prompt: &'static CancellingPrompt = Box::leak(Box::new(CancellingPrompt(OnceLock::new())));
+    let service = services().join("password").to_str().unwrap().to_owned();
+    let policy = PolicyBuilder::new()
+        .biometrics(None)
+        .prompt(prompt)
+        .pam_service(Box::leak(service.into_boxed_str()))
+        .build()
+        .unwrap();
+
+    let context = Context::new(());
+    prompt.0.set(context.handle()).unwrap();
+
+    assert!(matches!(
+        context.blocking_authenticate(TEXT, &policy),
+        Err(Error::AppCanceled)
+    ));
+}
+
+#[test]
+fn runtime_text_and_service() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let text = TextBuf::from(TEXT);
+    let policy = PolicyBuilder::new()
+        .biometrics(None)
+        .prompt(prompt)
+        .build()
+        .unwrap()
+        .with_pam_service(services().join("password").to_str().unwrap().to_owned())
+        .unwrap();
+
+    let result = Context::new(()).blocking_authenticate(text.as_text(), &policy.clone());
+
+    assert!(result.is_ok());
+    assert_eq!(prompt.shown()[0], "info: Unlock the test vault");
+}
+
+#[test]
+fn missing_service() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+
+    assert!(authenticate("missing", prompt).is_err());
+}
+
+#[test]
+fn invalid_service() {
+    assert_eq!(
+        PolicyBuilder::new().pam_service("").build().err(),
+        Some(PolicyError::InvalidPamService)
+    );
+    assert_eq!(
+        PolicyBuilder::new().pam_service("lo\0gin").build().err(),
+        Some(PolicyError::InvalidPamService)
+    );
+    assert_eq!(
+        PolicyBuilder::new()
+            .build()
+            .unwrap()
+            .with_pam_service(String::new())
+            .err(),
+        Some(PolicyError::InvalidPamService)
+    );
+}
+
+#[test]
+fn reuse_within_duration() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let policy = reusable("password", prompt, Duration::from_secs(60));
+    let context = Context::new(());
+
+    let first = context.blocking_authenticate(TEXT, &policy).unwrap();
+    let second = context.blocking_authenticate(TEXT, &policy).unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(prompt.shown().len(), 2);
+    // Other contexts have their own authentications.
+    assert!(Context::new(())
+        .blocking_authenticate(TEXT, &policy)
+        .is_ok());
+    assert_eq!(prompt.shown().len(), 4);
+}
+
+#[test]
+fn reuse_is_per_action() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let policy = reusable("password", prompt, Duration::from_secs(60));
+    let context = Context::new(());
+
+    assert!(context.blocking_authenticate(TEXT, &policy).is_ok());
+    let export_keys = policy
+        .clone()
+        .with_action_id("com.example.app.export-keys")
+        .unwrap();
+    assert!(context.blocking_authenticate(TEXT, &export_keys).is_ok());
+    assert!(context.blocking_authenticate(TEXT, &export_keys).is_ok());
+
+    assert_eq!(prompt.shown().len(), 4);
+}
+
+#[test]
+fn reuse_expires() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let policy = reusable("password", prompt, Duration::from_millis(100));
+    let context = Context::new(());
+
+    assert!(context.blocking_authenticate(TEXT, &policy).is_ok());
+    thread::sleep(Duration::from_millis(200));
+    assert!(context.blocking_authenticate(TEXT, &policy).is_ok());
+
+    assert_eq!(prompt.shown().len(), 4);
+}
+
+#[test]
+fn failure_forgets_reuse() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let password = reusable("password", prompt, Duration::from_secs(60));
+    let context = Context::new(());
+
+    assert!(context.blocking_authenticate(TEXT, &password).is_ok());
+    assert!(context
+        .blocking_authenticate(TEXT, &policy("deny", prompt))
+        .is_err());
+    assert!(context.blocking_authenticate(TEXT, &password).is_ok());
+
+    assert_eq!(prompt.shown().len(), 5);
+}
+
+#[test]
+fn forget_authentications() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let policy = reusable("password", prompt, Duration::from_secs(60));
+    let context = Context::new(());
+
+    assert!(context.blocking_authenticate(TEXT, &policy).is_ok());
+    context.forget_authentications();
+    assert!(context.blocking_authenticate(TEXT, &policy).is_ok());
+
+    assert_eq!(prompt.shown().len(), 4);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn correct_password_async() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let policy = policy("password", prompt);
+
+    let result = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(Context::new(()).authenticate(TEXT, &policy));
+
+    assert!(result.is_ok());
+}
@@ -0,0 +1,871 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+#[cfg(feature = "mock")]
+use common::{
+    pam::{current_user, PASSWORD},
+    prompt::TestPrompt,
+};
+use gio::{glib, prelude::*};
+#[cfg(feature = "mock")]
+use robius_authentication::AuthenticationMethod;
+use robius_authentication::{
+    linux, policy, AndroidText, Authentication, AvailabilityStatus, BiometricStrength, Context,
+    Error, LinuxText, PlatformError, Policy, PolicyBuilder, PolicyError, Text, WindowsText,
+};
+
+const AUTHORITY_INTERFACE: &str = r#"
+<node>
+  <interface name="org.freedesktop.PolicyKit1.Authority">
+    <method name="CheckAuthorization">
+      <arg type="(sa{sv})" name="subject" direction="in"/>
+      <arg type="s" name="action_id" direction="in"/>
+      <arg type="a{ss}" name="details" direction="in"/>
+      <arg type="u" name="flags" direction="in"/>
+      <arg type="s" name="cancellation_id" direction="in"/>
+      <arg type="(bba{ss})" name="result" direction="out"/>
+    </method>
+    <method name="CancelCheckAuthorization">
+      <arg type="s" name="cancellation_id" direction="in"/>
+    </method>
+    <method name="EnumerateActions">
+      <arg type="s" name="locale" direction="in"/>
+      <arg type="a(ssssssuuua{ss})" name="action_descriptions" direction="out"/>
+    </method>
+    <method name="RegisterAuthenticationAgent">
+      <arg type="(sa{sv})" name="subject" direction="in"/>
+      <arg type="s" name="locale" direction="in"/>
+      <arg type="s" name="object_path" direction="in"/>
+    </method>
+    <method name="UnregisterAuthenticationAgent">
+      <arg type="(sa{sv})" name="subject" direction="in"/>
+      <arg type="s" name="object_path" direction="in"/>
+    </method>
+    <property type="s" name="BackendName" access="read"/>
+    <property type="s" name="BackendVersion" access="read"/>
+    <property type="u" name="BackendFeatures" access="read"/>
+  </interface>
+</node>
+"#;
+
+const POLICY: Policy = policy! {
+    biometrics: Some(BiometricStrength::Strong),
+    password: true,
+    watch: true,
+};
+
+const TEXT: Text = Text {
+    android: AndroidText {
+        title: "Title",
+        subtitle: None,
+        description: None,
+    },
+    apple: "authenticate",
+    windows: WindowsText::new("Title", "Description").unwrap(),
+    linux: LinuxText {
+        message: "Unlock the test vault",
+    },
+};
+
+#[derive(Clone, Copy)]
+#[cfg_attr(not(all(feature = "async", feature = "mock")), allow(dead_code))]
+enum Reply {
+    Authorized,
+    NotAuthorized,
+    Dismissed,
+    Challenge,
+    /// Reply with a challenge until an authentication agent is registered, and
+    /// then authenticate using the agent.
+    Agent,
+    Error(&'static str),
+    /// Don't reply until the check is cancelled.
+    Pending,
+}
+
+#[cfg_attr(not(feature = "async"), allow(dead_code))]
+struct Request {
+    action_id: String,
+    details: HashMap<String, String>,
+    flags: u32,
+    cancellation_id: String,
+}
+
+struct Agent {
+    sender: String,
+    #[cfg_attr(not(feature = "mock"), allow(dead_code))]
+    subject: Subject,
+    object_path: String,
+    unregistered: bool,
+}
+
+#[derive(Default)]
+struct Authority {
+    reply: Option<Reply>,
+    requests: Vec<Request>,
+    cancelled: Vec<String>,
+    /// The last authentication agent that was registered.
+    agent: Option<Agent>,
+    /// Whether registering an authentication agent fails.
+    reject_agent: bool,
+    /// Whether the caller may pass details, i.e. is root or the action's
+    /// owner. Like polkitd, the authority rejects details from anyone else.
+    trusted: bool,
+    /// The declared actions, and their implicit authorization for active
+    /// sessions.
+    actions: Vec<(&'static str, u32)>,
+}
+
+type Subject = (String, HashMap<String, glib::Variant>);
+
+thread_local! {
+    /// Checks that haven't been replied to, keyed by cancellation ID.
+    static PENDING: RefCell<HashMap<String, gio::DBusMethodInvocation>> =
+        RefCell::new(HashMap::new());
+}
+
+static AUTHORITY: OnceLock<Mutex<Authority>> = OnceLock::new();
+
+/// Starts the mock authority, if it isn't running, and sets the reply to the
+/// next authorization check.
+///
+/// Returns `None` if the test should be skipped.
+fn authority(reply: Reply) -> Option<&'static Mutex<Authority>> {
+    let bus = common::system_bus()?;
+    let authority = AUTHORITY.get_or_init(|| {
+        common::serve(
+            bus,
+            "org.freedesktop.PolicyKit1",
+            vec![common::Object {
+                path: "/org/freedesktop/PolicyKit1/Authority",
+                interface: AUTHORITY_INTERFACE,
+                properties: vec![
+                    ("BackendName", "mock".to_variant()),
+                    ("BackendVersion", "0".to_variant()),
+                    ("BackendFeatures", 0u32.to_variant()),
+                ],
+                handler: Box::new(|method, parameters, invocation| match method {
+                    "CheckAuthorization" => check_authorization(parameters, invocation),
+                    "CancelCheckAuthorization" => {
+                        cancel_check_authorization(parameters, invocation)
+                    }
+                    "EnumerateActions" => enumerate_actions(invocation),
+                    "RegisterAuthenticationAgent" => register_agent(parameters, invocation),
+                    "UnregisterAuthenticationAgent" => unregister_agent(parameters, invocation),
+                    _ => unreachable!(),
+                }),
+            }],
+        );
+        Mutex::default()
+    });
+
+    let mut state = authority.lock().unwrap();
+    state.reply = Some(reply);
+    state.requests.clear();
+    state.cancelled.clear();
+    state.agent = None;
+    state.reject_agent = false;
+    state.trusted = false;
+    state.actions.clear();
+    drop(state);
+
+    Some(authority)
+}
+
+fn check_authorization(parameters: glib::Variant, invocation: gio::DBusMethodInvocation) {
+    type Parameters = (Subject, String, HashMap<String, String>, u32, String);
+
+    let (_, action_id, details, flags, cancellation_id) =
+        parameters.get::<Parameters>().expect("invalid parameters");
+
+    let mut state = AUTHORITY.get().unwrap().lock().unwrap();
+    let untrusted = !details.is_empty() && !state.trusted;
+    state.requests.push(Request {
+        action_id,
+        details,
+        flags,
+        cancellation_id: cancellation_id.clone(),
+    });
+    if untrusted {
+        invocation.return_dbus_error(
+            "org.freedesktop.PolicyKit1.Error.NotAuthorized",
+            "Only trusted callers (e.g. uid 0 or an action owner) can use CheckAuthorization() \
+             and pass details",
+        );
+        return;
+    }
+
+    let (authorized, challenge, details) = match state.reply.take().expect("unexpected check") {
+        Reply::Authorized => (true, false, HashMap::new()),
+        Reply::NotAuthorized => (false, false, HashMap::new()),
+        Reply::Dismissed => (
+            false,
+            false,
+            HashMap::from([("polkit.dismissed".to_owned(), "true".to_owned())]),
+        ),
+        Reply::Challenge => (false, true, HashMap::new()),
+        Reply::Agent => match state.agent.as_ref().filter(|agent| !agent.unregistered) {
+            Some(agent) => {
+                begin_authentication(agent, invocation);
+                return;
+            }
+            None => {
+                state.reply = Some(Reply::Agent);
+                (false, true, HashMap::new())
+            }
+        },
+        Reply::Error(name) => {
+            invocation.return_dbus_error(name, "mock error");
+            return;
+        }
+        Reply::Pending => {
+            PENDING.with(|pending| pending.borrow_mut().insert(cancellation_id, invocation));
+            return;
+        }
+    };
+    invocation.return_value(Some(&((authorized, challenge, details),).to_variant()));
+}
+
+fn cancel_check_authorization(parameters: glib::Variant, invocation: gio::DBusMethodInvocation) {
+    let (cancellation_id,) = parameters.get::<(String,)>().expect("invalid parameters");
+
+    if let Some(pending) = PENDING.with(|pending| pending.borrow_mut().remove(&cancellation_id)) {
+        pending.return_dbus_error("org.freedesktop.PolicyKit1.Error.Cancelled", "cancelled");
+    }
+    AUTHORITY
+        .get()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .cancelled
+        .push(cancellation_id);
+    invocation.return_value(None);
+}
+
+fn enumerate_actions(invocation: gio::DBusMethodInvocation) {
+    let actions = AUTHORITY
+        .get()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .actions
+        .iter()
+        .map(|&(action_id, implicit_active)| {
+            (
+                action_id,
+                "Description",
+                "Message",
+                "Vendor",
+                "https://example.com",
+                "",
+                implicit_active,
+                implicit_active,
+                implicit_active,
+                HashMap::<String, String>::new(),
+            )
+        })
+        .collect::<Vec<_>>();
+    invocation.return_value(Some(&(actions,).to_variant()));
+}
+
+fn register_agent(parameters: glib::Variant, invocation: gio::DBusMethodInvocation) {
+    let (subject, _, object_path) = parameters
+        .get::<(Subject, String, String)>()
+        .expect("invalid parameters");
+
+    let mut state = AUTHORITY.get().unwrap().lock().unwrap();
+    if state.reject_agent {
+        invocation.return_dbus_error(
+            "org.freedesktop.PolicyKit1.Error.Failed",
+            "an agent is already registered",
+        );
+        return;
+    }
+    state.agent = Some(Agent {
+        sender: invocation.sender().to_string(),
+        subject,
+        object_path,
+        unregistered: false,
+    });
+    invocation.return_value(None);
+}
+
+fn unregister_agent(parameters: glib::Variant, invocation: gio::DBusMethodInvocation) {
+    let (_, object_path) = parameters
+        .get::<(Subject, String)>()
+        .expect("invalid parameters");
+
+    let mut state = AUTHORITY.get().unwrap().lock().unwrap();
+    let agent = state.agent.as_mut().expect("no agent was registered");
+    assert_eq!(agent.object_path, object_path);
+    agent.unregistered = true;
+    drop(state);
+    invocation.return_value(None);
+}
+
+/// Asks the registered agent to authenticate the current user, and replies to
+/// the check depending on the outcome.
+fn begin_authentication(agent: &Agent, invocation: gio::DBusMethodInvocation) {
+    let uid = unsafe { libc::getuid() };
+    let identities = vec![(
+        "unix-user".to_owned(),
+        HashMap::from([("uid".to_owned(), uid.to_variant())]),
+    )];
+    let parameters = (
+        "rs.robius.authentication.authenticate",
+        "Authentication is required",
+        "",
+        HashMap::<String, String>::new(),
+        "cookie",
+        identities,
+    );
+
+    invocation.connection().call(
+        Some(&agent.sender),
+        &agent.object_path,
+        "org.freedesktop.PolicyKit1.AuthenticationAgent",
+        "BeginAuthentication",
+        Some(&parameters.to_variant()),
+        None,
+        gio::DBusCallFlags::NONE,
+        -1,
+        gio::Cancellable::NONE,
+        move |result| {
+            let details = HashMap::<String, String>::new();
+            let reply = match result {
+                Ok(_) => (true, false, details),
+                Err(e) if e.message().contains("PolicyKit1.Error.Cancelled") => (
+                    false,
+                    false,
+                    HashMap::from([("polkit.dismissed".to_owned(), "true".to_owned())]),
+                ),
+                Err(_) => (false, false, details),
+            };
+            invocation.return_value(Some(&(reply,).to_variant()));
+        },
+    );
+}
+
+fn authenticate(reply: Reply) -> Option<(Result<Authentication, Error>, Request)> {
+    authenticate_with(reply, &POLICY)
+}
+
+fn authenticate_with(
+    reply: Reply,
+    policy: &Policy,
+) -> Option<(Result<Authentication, Error>, Request)> {
+    let _lock = common::lock();
+    let authority = authority(reply)?;
+
+    let result = Context::new(()).blocking_authenticate(TEXT, policy);
+    let request = authority
+        .lock()
+        .unwrap()
+        .requests
+        .pop()
+        .expect("no request");
+
+    Some((result, request))
+}
+
+#[test]
+fn authorized() {
+    let Some((result, request)) = authenticate(Reply::Authorized) else {
+        return;
+    };
+
+    // The action was authorized without asking our agent to authenticate.
+    let authentication = result.unwrap();
+    assert_eq!(authentication.method, None);
+    assert_eq!(authentication.account, None);
+    assert_eq!(request.action_id, "rs.robius.authentication.authenticate");
+    assert_eq!(request.flags, 1, "user interaction wasn't allowed");
+    // polkit only accepts details from trusted callers.
+    assert!(request.details.is_empty());
+}
+
+#[test]
+fn untrusted_details_are_rejected() {
+    let _lock = common::lock();
+    let Some(authority) = authority(Reply::Authorized) else {
+        return;
+    };
+
+    let check = || {
+        let details = polkit::Details::new();
+        details.insert("polkit.message", Some("Unlock the test vault"));
+        polkit::Authority::sync(gio::Cancellable::NONE)
+            .unwrap()
+            .check_authorization_sync(
+                &polkit::UnixProcess::new(std::process::id() as i32),
+                "rs.robius.authentication.authenticate",
+                Some(&details),
+                polkit::CheckAuthorizationFlags::NONE,
+                gio::Cancellable::NONE,
+            )
+    };
+
+    let error = check().unwrap_err();
+    assert!(error.message().contains("Only trusted callers"));
+
+    authority.lock().unwrap().trusted = true;
+    assert!(check().unwrap().is_authorized());
+}
+
+#[test]
+fn not_authorized() {
+    let Some((result, _)) = authenticate(Reply::NotAuthorized) else {
+        return;
+    };
+    assert!(matches!(result, Err(Error::Authentication)));
+}
+
+#[test]
+fn dismissed() {
+    let Some((result, _)) = authenticate(Reply::Dismissed) else {
+        return;
+    };
+    assert!(matches!(result, Err(Error::UserCanceled)));
+}
+
+#[test]
+fn no_agent() {
+    let Some((result, _)) = authenticate(Reply::Challenge) else {
+        return;
+    };
+    assert!(matches!(result, Err(Error::NotInteractive)));
+}
+
+#[test]
+fn polkit_backend() {
+    let _lock = common::lock();
+    let Some(authority) = authority(Reply::Authorized) else {
+        return;
+    };
+
+    let context = Context::with_authenticator(linux::Polkit::new());
+    assert!(context.blocking_authenticate(TEXT, &POLICY).is_ok());
+    assert_eq!(authority.lock().unwrap().requests.len(), 1);
+
+    // A policy that doesn't allow passwords can't be authorized.
+    let fingerprint = PolicyBuilder::new().password(false).build().unwrap();
+    assert!(matches!(
+        context.blocking_authenticate(TEXT, &fingerprint),
+        Err(Error::Unavailable)
+    ));
+    assert_eq!(authority.lock().unwrap().requests.len(), 1);
+}
+
+/// Returns a policy whose password is verified by the crate's agent, using a
+/// fake helper that accepts [`PASSWORD`].
+///
+/// The helper can only be replaced with the `mock` feature.
+#[cfg(feature = "mock")]
+fn agent_policy(prompt: &'static TestPrompt) -> Policy {
+    use std::{fs, os::unix::fs::PermissionsExt, path::PathBuf};
+
+    static HELPER: OnceLock<PathBuf> = OnceLock::new();
+
+    let helper = HELPER.get_or_init(|| {
+        let helper = common::pam::services().join("polkit-agent-helper-1");
+        fs::write(
+            &helper,
+            format!(
+                "#!/bin/sh\n\
+                 read cookie\n\
+                 echo 'PAM_PROMPT_ECHO_OFF Password: '\n\
+                 read password\n\
+                 if [ \"$1\" = \"$(id -un)\" ] && [ \"$cookie\" = cookie ] \
+                 && [ \"$password\" = \"{PASSWORD}\" ]; then\n\
+                 echo SUCCESS\n\
+                 else\n\
+                 echo FAILURE\n\
+                 fi\n"
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&helper, fs::Permissions::from_mode(0o755)).unwrap();
+        helper
+    });
+    std::env::set_var("ROBIUS_POLKIT_AGENT_HELPER", helper);
+
+    PolicyBuilder::new()
+        .biometrics(None)
+        .prompt(prompt)
+        .build()
+        .unwrap()
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn agent() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let Some((result, _)) = authenticate_with(Reply::Agent, &agent_policy(prompt)) else {
+        return;
+    };
+
+    let authentication = result.unwrap();
+    assert_eq!(
+        authentication.method,
+        Some(AuthenticationMethod::Credential)
+    );
+    assert_eq!(authentication.account, Some(current_user()));
+    assert_eq!(
+        prompt.shown(),
+        ["info: Authentication is required", "secret: Password: "]
+    );
+
+    let state = AUTHORITY.get().unwrap().lock().unwrap();
+    let agent = state.agent.as_ref().expect("no agent was registered");
+    assert!(agent.unregistered, "agent wasn't unregistered");
+    assert_eq!(agent.subject.0, "unix-process");
+    assert_eq!(
+        agent.subject.1.get("pid").and_then(|pid| pid.get::<u32>()),
+        Some(std::process::id())
+    );
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn agent_registration_failure() {
+    let prompt = TestPrompt::new(Some(PASSWORD));
+    let policy = agent_policy(prompt);
+    let _lock = common::lock();
+    let Some(authority) = authority(Reply::Agent) else {
+        return;
+    };
+    authority.lock().unwrap().reject_agent = true;
+
+    let result = Context::new(()).blocking_authenticate(TEXT, &policy);
+
+    // The password isn't verified using PAM instead, which would bypass the
+    // action's authorization.
+    assert!(
+        matches!(result, Err(Error::Platform(PlatformError::GLib { .. }))),
+        "unexpected result: {result:?}"
+    );
+    assert!(prompt.shown().is_empty());
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn agent_incorrect_password() {
+    let prompt = TestPrompt::new(Some("hunter2"));
+    let Some((result, _)) = authenticate_with(Reply::Agent, &agent_policy(prompt)) else {
+        return;
+    };
+    assert!(matches!(result, Err(Error::Authentication)));
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn agent_canceled() {
+    let prompt = TestPrompt::new(None);
+    let Some((result, _)) = authenticate_with(Reply::Agent, &agent_policy(prompt)) else {
+        return;
+    };
+    assert!(matches!(result, Err(Error::UserCanceled)));
+}
+
+/// Returns the availability of a password-only policy when the authority
+/// declares `actions`.
+fn password_availability(actions: Vec<(&'static str, u32)>) -> Option<AvailabilityStatus> {
+    const PASSWORD: Policy = policy! {
+        biometrics: None,
+    };
+
+    let _lock = common::lock();
+    let authority = authority(Reply::Authorized)?;
+    authority.lock().unwrap().actions = actions;
+
+    let availability = Context::new(()).availability(&PASSWORD).unwrap();
+    assert!(
+        authority.lock().unwrap().requests.is_empty(),
+        "authorization was checked"
+    );
+    Some(availability.status)
+}
+
+#[test]
+fn available() {
+    const AUTH_SELF: u32 = 1;
+
+    let Some(status) = password_availability(vec![
+        ("org.example.other", 0),
+        ("rs.robius.authentication.authenticate", AUTH_SELF),
+    ]) else {
+        return;
+    };
+    assert_eq!(status, AvailabilityStatus::Available);
+}
+
+#[test]
+fn available_disabled_by_policy() {
+    const NO: u32 = 0;
+
+    let Some(status) = password_availability(vec![("rs.robius.authentication.authenticate", NO)])
+    else {
+        return;
+    };
+    assert_eq!(status, AvailabilityStatus::DisabledByPolicy);
+}
+
+#[test]
+fn available_undeclared_action() {
+    let Some(status) = password_availability(Vec::new()) else {
+        return;
+    };
+    assert_eq!(status, AvailabilityStatus::Unavailable);
+}
+
+/// Waits for the client to cancel the pending check, which it does
+/// asynchronously.
+fn wait_for_cancellation(authority: &Mutex<Authority>) {
+    use std::time::{Duration, Instant};
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let state = authority.lock().unwrap();
+        if let Some(cancelled) = state.cancelled.first() {
+            assert_eq!(*cancelled, state.requests[0].cancellation_id);
+            break;
+        }
+        drop(state);
+
+        assert!(Instant::now() < deadline, "check wasn't cancelled");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn handle_cancels_check() {
+    use std::time::Duration;
+
+    let _lock = common::lock();
+    let Some(authority) = authority(Reply::Pending) else {
+        return;
+    };
+
+    let context = Context::new(());
+    let handle = context.handle();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(200));
+        handle.cancel();
+    });
+    let result = context.blocking_authenticate(TEXT, &POLICY);
+    canceller.join().unwrap();
+
+    assert!(matches!(result, Err(Error::AppCanceled)));
+    wait_for_cancellation(authority);
+}
+
+#[test]
+fn handle_only_cancels_request_in_flight() {
+    let _lock = common::lock();
+    if authority(Reply::Authorized).is_none() {
+        return;
+    }
+
+    let context = Context::new(());
+    context.handle().cancel();
+
+    assert!(context.blocking_authenticate(TEXT, &POLICY).is_ok());
+}
+
+#[test]
+fn timeout_cancels_check() {
+    use std::time::Duration;
+
+    let _lock = common::lock();
+    let Some(authority) = authority(Reply::Pending) else {
+        return;
+    };
+
+    let policy = PolicyBuilder::new()
+        .timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    let result = Context::new(()).blocking_authenticate(TEXT, &policy);
+
+    assert!(matches!(result, Err(Error::Timeout)));
+    wait_for_cancellation(authority);
+}
+
+#[test]
+fn timeout_not_reached() {
+    use std::time::Duration;
+
+    let Some((result, _)) = authenticate_with(
+        Reply::Authorized,
+        &PolicyBuilder::new()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .unwrap(),
+    ) else {
+        return;
+    };
+    assert!(result.is_ok());
+}
+
+#[test]
+fn polkit_error() {
+    let Some((result, _)) = authenticate(Reply::Error(
+        "org.freedesktop.PolicyKit1.Error.NotAuthorized",
+    )) else {
+        return;
+    };
+    assert!(matches!(result, Err(Error::Authentication)));
+}
+
+#[test]
+fn polkit_failure() {
+    let Some((result, _)) = authenticate(Reply::Error("org.freedesktop.PolicyKit1.Error.Failed"))
+    else {
+        return;
+    };
+
+    let Err(error) = result else {
+        panic!("authentication succeeded");
+    };
+    let Some(PlatformError::GLib { domain, code, .. }) = error.platform() else {
+        panic!("unexpected error: {error:?}");
+    };
+    assert_eq!(domain, "polkit-error-quark");
+    assert_eq!(*code, 0);
+    assert!(error.to_string().contains("polkit-error-quark"));
+}
+
+#[test]
+fn custom_action_id() {
+    const EXPORT_KEYS: Policy = policy! {
+        action_id: "com.example.app.export-keys",
+    };
+
+    let Some((result, request)) = authenticate_with(Reply::Authorized, &EXPORT_KEYS) else {
+        return;
+    };
+
+    assert!(result.is_ok());
+    assert_eq!(request.action_id, "com.example.app.export-keys");
+}
+
+#[test]
+fn runtime_action_id() {
+    let app = String::from("com.example.app");
+    let policy = POLICY
+        .clone()
+        .with_action_id(format!("{app}.export-keys"))
+        .unwrap();
+
+    let Some((result, request)) = authenticate_with(Reply::Authorized, &policy) else {
+        return;
+    };
+
+    assert!(result.is_ok());
+    assert_eq!(request.action_id, "com.example.app.export-keys");
+}
+
+#[test]
+fn invalid_action_id() {
+    assert_eq!(
+        PolicyBuilder::new().action_id("").build().err(),
+        Some(PolicyError::InvalidActionId)
+    );
+    assert_eq!(
+        PolicyBuilder::new()
+            .action_id("com.example.app.unlock vault")
+            .build()
+            .err(),
+        Some(PolicyError::InvalidActionId)
+    );
+    assert_eq!(
+        POLICY
+            .clone()
+            .with_action_id("com.example.app.unlock vault".to_owned())
+            .err(),
+        Some(PolicyError::InvalidActionId)
+    );
+}
+
+#[test]
+fn password_or_biometrics_required() {
+    assert_eq!(
+        PolicyBuilder::new()
+            .biometrics(None)
+            .password(false)
+            .build()
+            .err(),
+        Some(PolicyError::NoMethod)
+    );
+}
+
+#[cfg(feature = "async")]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn authorized_async() {
+    let _lock = common::lock();
+    let Some(authority) = authority(Reply::Authorized) else {
+        return;
+    };
+
+    let result = block_on(Context::new(()).authenticate(TEXT, &POLICY));
+
+    assert!(result.is_ok());
+    assert_eq!(authority.lock().unwrap().requests.len(), 1);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn dropping_future_cancels_check() {
+    use std::time::Duration;
+
+    let _lock = common::lock();
+    let Some(authority) = authority(Reply::Pending) else {
+        return;
+    };
+
+    let context = Context::new(());
+    let result = block_on(async {
+        tokio::time::timeout(
+            Duration::from_millis(200),
+            context.authenticate(TEXT, &POLICY),
+        )
+        .await
+    });
+    assert!(result.is_err(), "check wasn't pending");
+    wait_for_cancellation(authority);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn timeout_cancels_check_async() {
+    use std::time::Duration;
+
+    let _lock = common::lock();
+    let Some(authority) = authority(Reply::Pending) else {
+        return;
+    };
+
+    let policy = PolicyBuilder::new()
+        .timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    let result = block_on(Context::new(()).authenticate(TEXT, &policy));
+
+    assert!(matches!(result, Err(Error::Timeout)));
+    wait_for_cancellation(authority);
+}
@@ -0,0 +1,160 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::{
+    fs::File,
+    io::{Read, Write},
+    os::{
+        fd::{FromRawFd, OwnedFd},
+        unix::process::CommandExt,
+    },
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::pam::{services, PASSWORD};
+use robius_authentication::{AndroidText, Context, LinuxText, PolicyBuilder, Text, WindowsText};
+
+const TEXT: Text = Text {
+    android: AndroidText {
+        title: "Title",
+        subtitle: None,
+        description: None,
+    },
+    apple: "authenticate",
+    windows: WindowsText::new("Title", "Description").unwrap(),
+    linux: LinuxText {
+        message: "Unlock the test vault",
+    },
+};
+
+/// The variable containing the PAM service used by [`child`].
+const SERVICE_VAR: &str = "ROBIUS_TTY_TEST_SERVICE";
+
+/// Authenticates on the controlling terminal set up by [`run_on_terminal`].
+///
+/// This does nothing unless run by [`run_on_terminal`].
+#[test]
+fn child() {
+    let Ok(service) = std::env::var(SERVICE_VAR) else {
+        return;
+    };
+
+    let policy = PolicyBuilder::new()
+        .biometrics(None)
+        .pam_service(Box::leak(service.into_boxed_str()))
+        .build()
+        .unwrap();
+    let result = Context::new(()).blocking_authenticate(TEXT, &policy);
+    println!("result: {:?}", result.map(|_| ()));
+}
+
+/// Runs [`child`] on a new pseudoterminal, typing `input` once it asks for a
+/// password, and returns everything written to the terminal.
+fn run_on_terminal(input: &[u8]) -> String {
+    let (mut master, slave) = openpty();
+
+    let mut command = Command::new(std::env::current_exe().unwrap());
+    command
+        .args(["child", "--exact", "--nocapture", "--test-threads=1"])
+        .env(SERVICE_VAR, services().join("password"))
+        // There is no polkit authority on this bus, so the terminal is used.
+        .env("DBUS_SYSTEM_BUS_ADDRESS", "unix:path=/nonexistent")
+        .stdin(Stdio::from(slave.try_clone().unwrap()))
+        .stdout(Stdio::from(slave.try_clone().unwrap()))
+        .stderr(Stdio::from(slave));
+    unsafe {
+        command.pre_exec(|| {
+            // Make the pseudoterminal the child's controlling terminal.
+            if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY, 0) == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        })
+    };
+    let mut child = command.spawn().unwrap();
+    // Close our copies of the slave, so reading the master fails once the child
+    // exits.
+    drop(command);
+
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let reader = {
+        let output = output.clone();
+        let mut master = master.try_clone().unwrap();
+        thread::spawn(move || {
+            let mut buffer = [0; 1024];
+            while let Ok(len @ 1..) = master.read(&mut buffer) {
+                output.lock().unwrap().extend_from_slice(&buffer[..len]);
+            }
+        })
+    };
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !String::from_utf8_lossy(&output.lock().unwrap()).contains("Password:") {
+        if Instant::now() > deadline {
+            let _ = child.kill();
+            panic!(
+                "child didn't ask for a password: {}",
+                String::from_utf8_lossy(&output.lock().unwrap())
+            );
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    master.write_all(input).unwrap();
+
+    assert!(child.wait().unwrap().success());
+    reader.join().unwrap();
+
+    let output = output.lock().unwrap();
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+fn openpty() -> (File, OwnedFd) {
+    let mut master = 0;
+    let mut slave = 0;
+    let result = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            std::ptr::null(),
+        )
+    };
+    assert_eq!(result, 0, "failed to open pseudoterminal");
+
+    unsafe { (File::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) }
+}
+
+#[test]
+fn correct_password() {
+    let output = run_on_terminal(format!("{PASSWORD}\r").as_bytes());
+
+    assert!(output.contains("Unlock the test vault"), "{output}");
+    assert!(output.contains("result: Ok(())"), "{output}");
+    assert!(!output.contains(PASSWORD), "password was echoed: {output}");
+}
+
+#[test]
+fn incorrect_password() {
+    let output = run_on_terminal(b"hunter2\r");
+
+    assert!(output.contains("result: Err(Authentication)"), "{output}");
+}
+
+#[test]
+fn editing() {
+    let output = run_on_terminal(format!("x{PASSWORD}yy\x7f\x7f\x15{PASSWORD}\r").as_bytes());
+
+    assert!(output.contains("result: Ok(())"), "{output}");
+}
+
+#[test]
+fn ctrl_c_cancels() {
+    let output = run_on_terminal(b"hun\x03");
+
+    assert!(output.contains("result: Err(UserCanceled)"), "{output}");
+}
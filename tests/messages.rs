@@ -0,0 +1,175 @@
+use robius_authentication::{Catalog, Error, ErrorMessage, PlatformError};
+
+fn german() -> Catalog {
+    Catalog::new()
+        .add(
+            "de",
+            [
+                (
+                    "not-enrolled",
+                    ErrorMessage::new(
+                        "Es sind keine Fingerabdrücke oder Gesichter registriert.",
+                        Some("Füge einen in den Einstellungen hinzu."),
+                    ),
+                ),
+                (
+                    "exhausted",
+                    ErrorMessage::new("Zu viele Fehlversuche.", None::<&str>),
+                ),
+            ],
+        )
+        .add(
+            "de-AT",
+            [(
+                "exhausted",
+                ErrorMessage::new("Zu viele Fehlversuche, bitte warten.", None::<&str>),
+            )],
+        )
+}
+
+#[test]
+fn english() {
+    let message = Error::NotEnrolled.message();
+    assert_eq!(message.message, "No fingerprints or faces are enrolled.");
+    assert_eq!(
+        message.hint.as_deref(),
+        Some("Add one in your device's settings, then try again.")
+    );
+
+    assert_eq!(
+        Error::PasscodeNotSet.message().message,
+        "No passcode is set on this device."
+    );
+    assert_eq!(
+        Error::Exhausted.message().message,
+        "There were too many failed attempts."
+    );
+    assert_eq!(Error::UserCanceled.message().hint, None);
+}
+
+#[test]
+fn every_error_has_its_own_message() {
+    let fallback = Error::Unknown.message();
+
+    for error in [
+        Error::Authentication,
+        Error::Exhausted,
+        Error::Unavailable,
+        Error::UserCanceled,
+        Error::AppCanceled,
+        Error::Timeout,
+        Error::SystemCanceled,
+        Error::BiometryDisconnected,
+        Error::NotPaired,
+        Error::NotEnrolled,
+        Error::NotInteractive,
+        Error::WatchNotAvailable,
+        Error::InvalidDimensions,
+        Error::UserFallback,
+        Error::PasscodeNotSet,
+        Error::UpdateRequired,
+        Error::Busy,
+        Error::DisabledByPolicy,
+        Error::NotConfigured,
+    ] {
+        assert_ne!(error.message(), fallback, "{}", error.message_id());
+    }
+    assert_eq!(
+        Error::NotInteractive.message().hint.as_deref(),
+        Some("Try again from a session where the prompt can be shown.")
+    );
+}
+
+#[test]
+fn english_is_the_default() {
+    let catalog = Catalog::new();
+
+    for error in [Error::NotEnrolled, Error::Busy, Error::Unknown] {
+        assert_eq!(catalog.message(&error, "en-US"), error.message());
+        assert_eq!(catalog.message(&error, "fr"), error.message());
+    }
+}
+
+#[test]
+fn platform_errors_share_a_message() {
+    let error = Error::Platform(PlatformError::Pam {
+        code: 4,
+        message: "System error".to_owned(),
+    });
+
+    assert_eq!(error.message_id(), "platform");
+    assert_eq!(
+        error.message().message,
+        "Something went wrong while signing in."
+    );
+}
+
+#[test]
+fn added_locale() {
+    let catalog = german();
+
+    assert_eq!(
+        catalog.message(&Error::NotEnrolled, "de").message,
+        "Es sind keine Fingerabdrücke oder Gesichter registriert."
+    );
+    assert_eq!(
+        catalog.message(&Error::Exhausted, "de-AT").message,
+        "Zu viele Fehlversuche, bitte warten."
+    );
+}
+
+#[test]
+fn falls_back_to_less_specific_locale() {
+    let catalog = german();
+
+    assert_eq!(
+        catalog.message(&Error::NotEnrolled, "de-AT").message,
+        "Es sind keine Fingerabdrücke oder Gesichter registriert."
+    );
+    assert_eq!(
+        catalog.message(&Error::Exhausted, "de-CH").message,
+        "Zu viele Fehlversuche."
+    );
+    assert_eq!(
+        catalog.message(&Error::Busy, "de-AT"),
+        Error::Busy.message()
+    );
+}
+
+#[test]
+fn posix_locale_names() {
+    let catalog = german();
+
+    assert_eq!(
+        catalog.message(&Error::Exhausted, "de_AT.UTF-8").message,
+        "Zu viele Fehlversuche, bitte warten."
+    );
+    assert_eq!(
+        catalog.message(&Error::Exhausted, "DE_at@euro").message,
+        "Zu viele Fehlversuche, bitte warten."
+    );
+    assert_eq!(
+        catalog.message(&Error::Exhausted, "C"),
+        Error::Exhausted.message()
+    );
+}
+
+#[test]
+fn add_replaces_messages() {
+    let catalog = german().add(
+        "de",
+        [(
+            "exhausted",
+            ErrorMessage::new("Zu viele Versuche.", Some("Später erneut versuchen.")),
+        )],
+    );
+
+    assert_eq!(
+        catalog.message(&Error::Exhausted, "de").message,
+        "Zu viele Versuche."
+    );
+    assert_eq!(
+        catalog.message(&Error::NotEnrolled, "de").hint.as_deref(),
+        Some("Füge einen in den Einstellungen hinzu.")
+    );
+}
@@ -0,0 +1,396 @@
+#![cfg(feature = "mock")]
+
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use robius_authentication::{
+    mock::{MockBackend, Outcome},
+    AndroidText, AuthEvent, AuthenticationMethod, Availability, AvailabilityStatus,
+    BiometricStrength, BiometryKind, Context, Error, LinuxText, Policy, PolicyBuilder, Text,
+    WindowsText,
+};
+
+const TEXT: Text = Text {
+    android: AndroidText {
+        title: "Title",
+        subtitle: None,
+        description: None,
+    },
+    apple: "authenticate",
+    windows: WindowsText::new("Title", "Description").unwrap(),
+    linux: LinuxText {
+        message: "Unlock the test vault",
+    },
+};
+
+fn policy() -> Policy {
+    PolicyBuilder::new()
+        .biometrics(Some(BiometricStrength::Strong))
+        .password(true)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn outcomes_are_returned_in_order() {
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Biometric));
+    backend.push(Outcome::error(Error::UserCanceled));
+    let context = Context::mock(&backend);
+
+    let authentication = context.blocking_authenticate(TEXT, &policy()).unwrap();
+    assert_eq!(authentication.method, Some(AuthenticationMethod::Biometric));
+    assert!(matches!(
+        context.blocking_authenticate(TEXT, &policy()),
+        Err(Error::UserCanceled)
+    ));
+    assert_eq!(backend.pending(), 0);
+}
+
+#[test]
+fn requests_are_recorded() {
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Credential));
+    let policy = policy()
+        .with_action_id("com.example.app.export-keys")
+        .unwrap();
+
+    assert!(Context::mock(&backend)
+        .blocking_authenticate(TEXT, &policy)
+        .is_ok());
+
+    let requests = backend.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].text.linux.message, "Unlock the test vault");
+    assert_eq!(requests[0].text.android.title, "Title");
+    assert_eq!(
+        requests[0].policy.biometrics(),
+        Some(BiometricStrength::Strong)
+    );
+    assert!(requests[0].policy.password());
+    assert_eq!(
+        requests[0].policy.action_id(),
+        "com.example.app.export-keys"
+    );
+}
+
+#[test]
+fn exhausted_after_attempts() {
+    let backend = MockBackend::new();
+    backend.push(Outcome::exhausted_after(3).event(AuthEvent::Help("Too many attempts".into())));
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut context = Context::mock(&backend);
+    let reported = events.clone();
+    context.set_event_handler(move |event| reported.lock().unwrap().push(event));
+
+    assert!(matches!(
+        context.blocking_authenticate(TEXT, &policy()),
+        Err(Error::Exhausted)
+    ));
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            AuthEvent::AttemptFailed,
+            AuthEvent::AttemptFailed,
+            AuthEvent::AttemptFailed,
+            AuthEvent::Help("Too many attempts".into()),
+        ]
+    );
+}
+
+#[test]
+fn handle_cancels_delay() {
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Biometric).delay(Duration::from_secs(60)));
+    let context = Context::mock(&backend);
+
+    let handle = context.handle();
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        handle.cancel();
+    });
+    let result = context.blocking_authenticate(TEXT, &policy());
+    canceller.join().unwrap();
+
+    assert!(matches!(result, Err(Error::AppCanceled)));
+}
+
+#[test]
+fn handle_only_cancels_request_in_flight() {
+    let backend = MockBackend::new();
+    backend
+        .push(Outcome::success(AuthenticationMethod::Biometric).delay(Duration::from_millis(50)));
+    let context = Context::mock(&backend);
+
+    context.handle().cancel();
+
+    assert!(context.blocking_authenticate(TEXT, &policy()).is_ok());
+}
+
+#[test]
+fn timeout_cancels_delay() {
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Biometric).delay(Duration::from_secs(60)));
+    let policy = PolicyBuilder::new()
+        .timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+
+    assert!(matches!(
+        Context::mock(&backend).blocking_authenticate(TEXT, &policy),
+        Err(Error::Timeout)
+    ));
+}
+
+#[test]
+fn reused_authentications_skip_the_backend() {
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Biometric));
+    let policy = PolicyBuilder::new()
+        .reuse_duration(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    let context = Context::mock(&backend);
+
+    assert!(context.blocking_authenticate(TEXT, &policy).is_ok());
+    assert!(context.blocking_authenticate(TEXT, &policy).is_ok());
+
+    assert_eq!(backend.requests().len(), 1);
+}
+
+/// Returns a reusable policy that allows `biometrics` and passwords, and
+/// requires both if `require_all` is set.
+#[cfg(target_os = "linux")]
+fn reusable(biometrics: Option<BiometricStrength>, require_all: bool) -> Policy {
+    PolicyBuilder::new()
+        .biometrics(biometrics)
+        .require_all(require_all)
+        .reuse_duration(Duration::from_secs(60))
+        .build()
+        .unwrap()
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn weaker_authentications_arent_reused() {
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Credential));
+    backend.push(Outcome::success(AuthenticationMethod::Credential));
+    let context = Context::mock(&backend);
+
+    let password = reusable(None, false);
+    assert!(context.blocking_authenticate(TEXT, &password).is_ok());
+    let all = reusable(Some(BiometricStrength::Strong), true);
+    assert!(context.blocking_authenticate(TEXT, &all).is_ok());
+    assert_eq!(backend.requests().len(), 2);
+
+    // Every method was verified, which is enough for the password policy.
+    assert!(context.blocking_authenticate(TEXT, &password).is_ok());
+    assert!(context.blocking_authenticate(TEXT, &all).is_ok());
+    assert_eq!(backend.requests().len(), 2);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn weaker_biometrics_arent_reused() {
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Biometric));
+    backend.push(Outcome::success(AuthenticationMethod::Biometric));
+    let context = Context::mock(&backend);
+
+    let weak = reusable(Some(BiometricStrength::Weak), false);
+    assert!(context.blocking_authenticate(TEXT, &weak).is_ok());
+    let strong = reusable(Some(BiometricStrength::Strong), false);
+    assert!(context.blocking_authenticate(TEXT, &strong).is_ok());
+    assert!(context.blocking_authenticate(TEXT, &weak).is_ok());
+
+    assert_eq!(backend.requests().len(), 2);
+}
+
+#[test]
+fn availability() {
+    let backend = MockBackend::new();
+    let context = Context::mock(&backend);
+
+    assert!(context.availability(&policy()).unwrap().is_available());
+
+    let availability = Availability {
+        status: AvailabilityStatus::NotEnrolled,
+        biometry: Some(BiometryKind::Face),
+    };
+    backend.set_availability(availability);
+    assert_eq!(context.availability(&policy()).unwrap(), availability);
+}
+
+#[test]
+#[should_panic = "no mock outcome is queued"]
+fn panics_without_outcome() {
+    let backend = MockBackend::new();
+
+    let _ = Context::mock(&backend).blocking_authenticate(TEXT, &policy());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_matches_blocking() {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context as TaskContext, Poll, Waker},
+    };
+
+    // Outcomes without a delay are ready as soon as the future is polled.
+    fn poll<F: Future>(future: F) -> F::Output {
+        match pin!(future).poll(&mut TaskContext::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("mock future is pending"),
+        }
+    }
+
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Watch));
+    backend.push(Outcome::error(Error::UserCanceled));
+    let context = Context::mock(&backend);
+
+    let authentication = poll(context.authenticate(TEXT, &policy())).unwrap();
+    assert_eq!(authentication.method, Some(AuthenticationMethod::Watch));
+    assert!(matches!(
+        poll(context.authenticate(TEXT, &policy())),
+        Err(Error::UserCanceled)
+    ));
+    assert_eq!(backend.requests().len(), 2);
+}
+
+/// Polls `future` on the current thread until it completes, parking the
+/// thread while it's pending.
+#[cfg(feature = "async")]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::{
+        pin::pin,
+        task::{Context as TaskContext, Poll, Wake, Waker},
+        thread::Thread,
+    };
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut TaskContext::from_waker(&waker)) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_delay_doesnt_block() {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context as TaskContext, Poll, Waker},
+    };
+
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Biometric).delay(Duration::from_secs(60)));
+    let context = Context::mock(&backend);
+    let policy = policy();
+
+    let mut future = pin!(context.authenticate(TEXT, &policy));
+    let mut cx = TaskContext::from_waker(Waker::noop());
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+
+    context.handle().cancel();
+    assert!(matches!(
+        future.as_mut().poll(&mut cx),
+        Poll::Ready(Err(Error::AppCanceled))
+    ));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_delay_elapses() {
+    let backend = MockBackend::new();
+    backend
+        .push(Outcome::success(AuthenticationMethod::Biometric).delay(Duration::from_millis(50)));
+    let context = Context::mock(&backend);
+
+    let authentication = block_on(context.authenticate(TEXT, &policy())).unwrap();
+    assert_eq!(authentication.method, Some(AuthenticationMethod::Biometric));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_handle_cancels_delay() {
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Biometric).delay(Duration::from_secs(60)));
+    let context = Context::mock(&backend);
+
+    let handle = context.handle();
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        handle.cancel();
+    });
+    let start = std::time::Instant::now();
+    let result = block_on(context.authenticate(TEXT, &policy()));
+    canceller.join().unwrap();
+
+    assert!(matches!(result, Err(Error::AppCanceled)));
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_timeout_cancels_delay() {
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Biometric).delay(Duration::from_secs(60)));
+    let policy = PolicyBuilder::new()
+        .timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+
+    assert!(matches!(
+        block_on(Context::mock(&backend).authenticate(TEXT, &policy)),
+        Err(Error::Timeout)
+    ));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn dropping_future_ends_request() {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context as TaskContext, Waker},
+    };
+
+    let backend = MockBackend::new();
+    backend.push(Outcome::success(AuthenticationMethod::Biometric).delay(Duration::from_secs(60)));
+    backend.push(Outcome::success(AuthenticationMethod::Credential));
+    let context = Context::mock(&backend);
+    let policy = policy();
+
+    {
+        let mut future = pin!(context.authenticate(TEXT, &policy));
+        let mut cx = TaskContext::from_waker(Waker::noop());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+    }
+    // Cancelling has no effect once the request has ended.
+    context.handle().cancel();
+
+    let authentication = block_on(context.authenticate(TEXT, &policy)).unwrap();
+    assert_eq!(
+        authentication.method,
+        Some(AuthenticationMethod::Credential)
+    );
+}
@@ -0,0 +1,180 @@
+use robius_authentication::packaging::{
+    is_valid_action_id, validate_polkit_policy, Action, Authorization, Declaration, Defaults,
+    ValidationError,
+};
+
+const UNLOCK_VAULT: &str = "com.example.app.unlock-vault";
+const EXPORT_KEYS: &str = "com.example.app.export-keys";
+
+const DECLARATION: Declaration = Declaration {
+    vendor: "Example & Co",
+    vendor_url: Some("https://example.com"),
+    icon_name: Some("dialog-password"),
+    usage_description: "Unlock your <vault> with Face ID",
+    actions: &[
+        Action {
+            id: UNLOCK_VAULT,
+            description: "Unlock the vault",
+            message: "Authentication is required to unlock the vault",
+            defaults: Defaults::all(Authorization::AuthSelfKeep),
+            annotations: &[],
+        },
+        Action {
+            id: EXPORT_KEYS,
+            description: "Export private keys",
+            message: "Authentication is required to export private keys",
+            defaults: Defaults {
+                any: Authorization::No,
+                inactive: Authorization::No,
+                active: Authorization::AuthAdmin,
+            },
+            annotations: &[("org.freedesktop.policykit.imply", UNLOCK_VAULT)],
+        },
+    ],
+};
+
+#[test]
+fn generated_policy_is_valid() {
+    let policy = DECLARATION.polkit_policy();
+
+    assert_eq!(
+        validate_polkit_policy(&policy, &[UNLOCK_VAULT, EXPORT_KEYS]),
+        Ok(())
+    );
+    assert!(policy.contains("<vendor>Example &amp; Co</vendor>"));
+    assert!(policy.contains("<allow_active>auth_admin</allow_active>"));
+    assert!(policy.contains(
+        "<annotate key=\"org.freedesktop.policykit.imply\">com.example.app.unlock-vault</annotate>"
+    ));
+}
+
+#[test]
+fn undeclared_action() {
+    let policy = DECLARATION.polkit_policy();
+
+    assert_eq!(
+        validate_polkit_policy(&policy, &["com.example.app.delete-vault"]),
+        Err(ValidationError::UndeclaredAction(
+            "com.example.app.delete-vault".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn invalid_action_id() {
+    let declaration = Declaration {
+        actions: &[Action {
+            id: "com.example.app_vault",
+            ..DECLARATION.actions[0].clone()
+        }],
+        ..DECLARATION
+    };
+
+    assert_eq!(
+        validate_polkit_policy(&declaration.polkit_policy(), &[]),
+        Err(ValidationError::InvalidActionId(
+            "com.example.app_vault".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn action_id_must_start_with_letter() {
+    assert!(is_valid_action_id("com.example.app-2.unlock"));
+    assert!(is_valid_action_id(
+        "org.freedesktop.NetworkManager.network-control"
+    ));
+    assert!(is_valid_action_id("a"));
+
+    for id in ["", "1foo", ".foo", "-foo", "com.example.app_vault"] {
+        assert!(!is_valid_action_id(id), "{id:?} is valid");
+    }
+}
+
+#[test]
+fn duplicate_action() {
+    let declaration = Declaration {
+        actions: &[
+            DECLARATION.actions[0].clone(),
+            DECLARATION.actions[0].clone(),
+        ],
+        ..DECLARATION
+    };
+
+    assert_eq!(
+        validate_polkit_policy(&declaration.polkit_policy(), &[]),
+        Err(ValidationError::DuplicateAction(UNLOCK_VAULT.to_owned()))
+    );
+}
+
+#[test]
+fn handwritten_policy() {
+    let policy = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE policyconfig PUBLIC
+ "-//freedesktop//DTD PolicyKit Policy Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/PolicyKit/1/policyconfig.dtd">
+<policyconfig>
+  <!-- Unlocking doesn't need an administrator. -->
+  <action id='com.example.app.unlock-vault'>
+    <description>Unlock the vault</description>
+    <message xml:lang="de">Authentifizierung erforderlich</message>
+    <defaults>
+      <allow_active>sometimes</allow_active>
+    </defaults>
+  </action>
+</policyconfig>
+"#;
+
+    assert_eq!(
+        validate_polkit_policy(policy, &[UNLOCK_VAULT]),
+        Err(ValidationError::InvalidAuthorization {
+            action: UNLOCK_VAULT.to_owned(),
+            value: "sometimes".to_owned(),
+        })
+    );
+}
+
+#[test]
+fn annotation_without_key() {
+    let policy = r#"<policyconfig>
+  <action id="com.example.app.unlock-vault">
+    <description>Unlock the vault</description>
+    <message>Authentication is required to unlock the vault</message>
+    <annotate>com.example.app.export-keys</annotate>
+  </action>
+</policyconfig>
+"#;
+
+    assert_eq!(
+        validate_polkit_policy(policy, &[UNLOCK_VAULT]),
+        Err(ValidationError::MissingAnnotationKey(
+            UNLOCK_VAULT.to_owned()
+        ))
+    );
+}
+
+#[test]
+fn malformed_policy() {
+    let policy = "<policyconfig><action id=\"a\"></policyconfig>";
+
+    assert!(matches!(
+        validate_polkit_policy(policy, &[]),
+        Err(ValidationError::Syntax(_))
+    ));
+    assert_eq!(
+        validate_polkit_policy("<policy/>", &[]),
+        Err(ValidationError::NotPolicyConfig)
+    );
+}
+
+#[test]
+fn apple_and_android_metadata() {
+    assert_eq!(
+        DECLARATION.info_plist(),
+        "<key>NSFaceIDUsageDescription</key>\n\
+         <string>Unlock your &lt;vault&gt; with Face ID</string>\n"
+    );
+    assert!(DECLARATION
+        .android_manifest()
+        .contains("android.permission.USE_BIOMETRIC"));
+}
@@ -0,0 +1,190 @@
+use robius_authentication::{
+    AndroidAuthenticators, ApplePolicy, BiometricStrength, LinuxPolicy, PolicyBuilder, PolicyError,
+    PolicyMapping, Target,
+};
+
+const ALL: PolicyBuilder = PolicyBuilder::new();
+
+const BIOMETRICS: PolicyBuilder = PolicyBuilder::new().password(false).watch(false);
+
+const PASSWORD: PolicyBuilder = PolicyBuilder::new().biometrics(None).watch(false);
+
+const NOTHING: PolicyBuilder = PolicyBuilder::new()
+    .biometrics(None)
+    .password(false)
+    .watch(false);
+
+#[test]
+fn apple() {
+    let mapping = |builder: PolicyBuilder| builder.mapping(Target::Apple);
+
+    assert_eq!(
+        mapping(ALL),
+        Ok(PolicyMapping::Apple(ApplePolicy::DeviceOwnerAuthentication))
+    );
+    assert_eq!(
+        mapping(BIOMETRICS),
+        Ok(PolicyMapping::Apple(
+            ApplePolicy::DeviceOwnerAuthenticationWithBiometrics
+        ))
+    );
+    assert_eq!(
+        mapping(BIOMETRICS.watch(true)),
+        Ok(PolicyMapping::Apple(
+            ApplePolicy::DeviceOwnerAuthenticationWithBiometricsOrWatch
+        ))
+    );
+    assert_eq!(
+        mapping(NOTHING.watch(true)),
+        Ok(PolicyMapping::Apple(
+            ApplePolicy::DeviceOwnerAuthenticationWithWatch
+        ))
+    );
+    assert_eq!(mapping(NOTHING), Err(PolicyError::NoMethod));
+    assert_eq!(
+        mapping(PASSWORD),
+        Err(PolicyError::PasswordWithoutBiometrics)
+    );
+    assert_eq!(
+        mapping(ALL.watch(false)),
+        Err(PolicyError::PasswordWithoutWatch)
+    );
+}
+
+#[test]
+fn watchos() {
+    assert_eq!(
+        ALL.mapping(Target::WatchOs),
+        Ok(PolicyMapping::Apple(
+            ApplePolicy::DeviceOwnerAuthenticationWithWristDetection
+        ))
+    );
+    assert_eq!(
+        ALL.wrist_detection(false).mapping(Target::WatchOs),
+        Ok(PolicyMapping::Apple(ApplePolicy::DeviceOwnerAuthentication))
+    );
+    assert_eq!(
+        BIOMETRICS.mapping(Target::WatchOs),
+        Err(PolicyError::PasswordRequired)
+    );
+}
+
+#[test]
+fn android() {
+    let Ok(PolicyMapping::Android(authenticators)) = ALL.mapping(Target::Android) else {
+        panic!("policy isn't valid for Android");
+    };
+    assert_eq!(authenticators.bits(), 0x800f);
+    assert!(authenticators.contains(AndroidAuthenticators::BIOMETRIC_STRONG));
+    assert!(authenticators.contains(AndroidAuthenticators::DEVICE_CREDENTIAL));
+
+    assert_eq!(
+        BIOMETRICS
+            .biometrics(Some(BiometricStrength::Weak))
+            .mapping(Target::Android),
+        Ok(PolicyMapping::Android(
+            AndroidAuthenticators::BIOMETRIC_WEAK
+        ))
+    );
+    assert_eq!(
+        PASSWORD.mapping(Target::Android),
+        Err(PolicyError::BiometricsRequired)
+    );
+}
+
+#[test]
+fn linux() {
+    assert_eq!(
+        PASSWORD
+            .action_id("com.example.app.unlock")
+            .pam_service("login")
+            .mapping(Target::Linux),
+        Ok(PolicyMapping::Linux(LinuxPolicy {
+            biometrics: false,
+            password: true,
+            action_id: "com.example.app.unlock",
+            pam_service: Some("login"),
+            require_all: false,
+        }))
+    );
+    assert_eq!(
+        BIOMETRICS.mapping(Target::Linux),
+        Ok(PolicyMapping::Linux(LinuxPolicy {
+            biometrics: true,
+            password: false,
+            action_id: "rs.robius.authentication.authenticate",
+            pam_service: None,
+            require_all: false,
+        }))
+    );
+    assert_eq!(NOTHING.mapping(Target::Linux), Err(PolicyError::NoMethod));
+    for action_id in ["", "1foo", ".foo", "-foo"] {
+        assert_eq!(
+            ALL.action_id(action_id).mapping(Target::Linux),
+            Err(PolicyError::InvalidActionId)
+        );
+    }
+    assert!(ALL
+        .action_id("org.freedesktop.NetworkManager.network-control")
+        .mapping(Target::Linux)
+        .is_ok());
+    assert_eq!(
+        ALL.pam_service("").mapping(Target::Linux),
+        Err(PolicyError::InvalidPamService)
+    );
+}
+
+#[test]
+fn windows() {
+    assert_eq!(ALL.mapping(Target::Windows), Ok(PolicyMapping::Windows));
+    assert_eq!(
+        PASSWORD.mapping(Target::Windows),
+        Err(PolicyError::BiometricsRequired)
+    );
+    assert_eq!(
+        BIOMETRICS.mapping(Target::Windows),
+        Err(PolicyError::PasswordRequired)
+    );
+}
+
+#[test]
+fn require_all() {
+    let Ok(PolicyMapping::Linux(policy)) = ALL.require_all(true).mapping(Target::Linux) else {
+        panic!("policy isn't valid for Linux");
+    };
+    assert!(policy.require_all);
+
+    // Requiring every method makes no sense if the policy only allows one.
+    for builder in [BIOMETRICS, PASSWORD] {
+        assert_eq!(
+            builder.require_all(true).mapping(Target::Linux),
+            Err(PolicyError::RequireAllSingleMethod)
+        );
+    }
+
+    for target in [
+        Target::Android,
+        Target::Apple,
+        Target::WatchOs,
+        Target::Windows,
+    ] {
+        assert_eq!(
+            ALL.require_all(true).mapping(target),
+            Err(PolicyError::RequireAllUnsupported)
+        );
+    }
+}
+
+#[test]
+fn build_uses_current_target() {
+    let current = Target::current().map(|target| ALL.mapping(target).map(|_| ()));
+
+    match current {
+        Some(result) => assert_eq!(ALL.build().map(|_| ()), result),
+        None => assert_eq!(ALL.build().err(), Some(PolicyError::UnsupportedTarget)),
+    }
+    assert_eq!(
+        PolicyError::NoMethod.to_string(),
+        PolicyError::NoMethod.message()
+    );
+}
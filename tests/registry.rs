@@ -0,0 +1,211 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard},
+};
+
+use robius_authentication::{
+    Authentication, AuthenticationRequest, Authenticator, Availability, Context, Error, Policy,
+    Registry, Result, SelectionReason, BACKEND_VAR,
+};
+
+/// A backend that can only be used if `usable` is set.
+#[derive(Debug)]
+struct Backend {
+    usable: bool,
+}
+
+impl Authenticator for Backend {
+    fn blocking_authenticate(&self, _: AuthenticationRequest<'_>) -> Result<Authentication> {
+        Err(Error::Unavailable)
+    }
+
+    fn availability(&self, _: &Policy) -> Result<Availability> {
+        Err(Error::Unavailable)
+    }
+
+    fn probe(&self) -> Result<()> {
+        if self.usable {
+            Ok(())
+        } else {
+            Err(Error::NotInteractive)
+        }
+    }
+
+    fn cancel(&self) {}
+}
+
+const USABLE: Backend = Backend { usable: true };
+const UNUSABLE: Backend = Backend { usable: false };
+
+/// Serializes the tests, which read the environment, and sets the override
+/// variable to `value`.
+fn environment(value: Option<&str>) -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    match value {
+        Some(value) => env::set_var(BACKEND_VAR, value),
+        None => env::remove_var(BACKEND_VAR),
+    }
+    lock
+}
+
+fn registry() -> Registry {
+    Registry::new()
+        .config_file(None)
+        .register("low", 1, USABLE)
+        .register("high", 3, UNUSABLE)
+        .register("middle", 2, USABLE)
+        .register("also-middle", 2, USABLE)
+}
+
+fn config_file(name: &str, contents: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!(
+        "robius-authentication-{}-{name}",
+        std::process::id()
+    ));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn priority() {
+    let _lock = environment(None);
+
+    assert_eq!(registry().names(), ["high", "middle", "also-middle", "low"]);
+
+    let context = Context::from_registry(registry()).unwrap();
+    let selection = context.selection().unwrap();
+    assert_eq!(selection.name, "middle");
+    let SelectionReason::Priority { skipped, unknown } = &selection.reason else {
+        panic!("unexpected reason: {:?}", selection.reason);
+    };
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].0, "high");
+    assert!(matches!(skipped[0].1, Error::NotInteractive));
+    assert_eq!(*unknown, None);
+}
+
+#[test]
+fn set_priority() {
+    let _lock = environment(None);
+    let registry = registry()
+        .set_priority("low", 10)
+        .set_priority("missing", 10);
+
+    assert_eq!(registry.names()[0], "low");
+    let context = Context::from_registry(registry).unwrap();
+    assert_eq!(context.selection().unwrap().name, "low");
+}
+
+#[test]
+fn register_replaces() {
+    let _lock = environment(None);
+    let registry = registry().register("high", 3, USABLE);
+
+    assert_eq!(registry.names().len(), 4);
+    let context = Context::from_registry(registry).unwrap();
+    assert_eq!(context.selection().unwrap().name, "high");
+}
+
+#[test]
+fn none_usable() {
+    let _lock = environment(None);
+    let registry = Registry::new()
+        .config_file(None)
+        .register("only", 1, UNUSABLE);
+
+    assert!(matches!(
+        Context::from_registry(registry),
+        Err(Error::Unavailable)
+    ));
+    assert!(matches!(
+        Context::from_registry(Registry::new().config_file(None)),
+        Err(Error::Unavailable)
+    ));
+}
+
+#[test]
+fn environment_override() {
+    let _lock = environment(Some("high"));
+
+    // The named backend is used even though its probe fails.
+    let context = Context::from_registry(registry()).unwrap();
+    let selection = context.selection().unwrap();
+    assert_eq!(selection.name, "high");
+    assert!(matches!(selection.reason, SelectionReason::Environment));
+}
+
+#[test]
+fn unknown_override() {
+    let _lock = environment(Some("missing"));
+
+    let context = Context::from_registry(registry()).unwrap();
+    let selection = context.selection().unwrap();
+    assert_eq!(selection.name, "middle");
+    let SelectionReason::Priority { unknown, .. } = &selection.reason else {
+        panic!("unexpected reason: {:?}", selection.reason);
+    };
+    assert_eq!(unknown.as_deref(), Some("missing"));
+}
+
+#[test]
+fn config_file_override() {
+    let _lock = environment(None);
+    let path = config_file("config", "# The backend to use.\n\n  low  \nhigh\n");
+
+    let context = Context::from_registry(registry().config_file(Some(path.clone()))).unwrap();
+    let selection = context.selection().unwrap();
+    assert_eq!(selection.name, "low");
+    assert!(matches!(
+        &selection.reason,
+        SelectionReason::ConfigFile(reason) if *reason == path
+    ));
+
+    // The environment takes precedence over the config file.
+    drop(_lock);
+    let _lock = environment(Some("also-middle"));
+    let context = Context::from_registry(registry().config_file(Some(path.clone()))).unwrap();
+    assert_eq!(context.selection().unwrap().name, "also-middle");
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn missing_config_file() {
+    let _lock = environment(None);
+    let path = env::temp_dir().join("robius-authentication-missing");
+
+    let context = Context::from_registry(registry().config_file(Some(path))).unwrap();
+    let selection = context.selection().unwrap();
+    assert_eq!(selection.name, "middle");
+}
+
+#[test]
+fn system() {
+    let _lock = environment(None);
+
+    let context = Context::from_registry(Registry::system(())).unwrap();
+    assert_eq!(context.selection().unwrap().name, "system");
+    assert!(Context::new(()).selection().is_none());
+    assert!(Context::with_authenticator(USABLE).selection().is_none());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn linux() {
+    let _lock = environment(Some("tty"));
+
+    assert_eq!(
+        Registry::system(()).names(),
+        ["system", "polkit", "fprintd", "pam", "tty"]
+    );
+    let context = Context::from_registry(Registry::system(())).unwrap();
+    let selection = context.selection().unwrap();
+    assert_eq!(selection.name, "tty");
+    assert!(matches!(selection.reason, SelectionReason::Environment));
+
+    // The override only applies to contexts created from a registry.
+    assert!(Context::new(()).selection().is_none());
+}
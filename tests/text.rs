@@ -0,0 +1,66 @@
+use robius_authentication::{
+    AndroidText, AndroidTextBuf, LinuxText, LinuxTextBuf, Text, TextBuf, WindowsText,
+    WindowsTextBuf,
+};
+
+const TEXT: Text = Text {
+    android: AndroidText {
+        title: "Title",
+        subtitle: Some("Subtitle"),
+        description: None,
+    },
+    apple: "authenticate",
+    windows: WindowsText::new("Title", "Description").unwrap(),
+    linux: LinuxText {
+        message: "Unlock the test vault",
+    },
+};
+
+#[test]
+fn windows_text_length_is_checked_on_every_target() {
+    let title = "t".repeat(128);
+    let description = "d".repeat(1024);
+
+    assert!(WindowsText::new(&title, &description).is_some());
+    assert!(WindowsText::new(&format!("{title}t"), &description).is_none());
+    assert!(WindowsText::new(&title, &format!("{description}d")).is_none());
+
+    assert!(WindowsTextBuf::new(title.clone(), description.clone()).is_some());
+    assert!(WindowsTextBuf::new(title + "t", description).is_none());
+}
+
+#[test]
+fn text_buf_round_trips() {
+    let text = TextBuf::from(TEXT);
+    let text = text.as_text();
+
+    assert_eq!(text.android.title, "Title");
+    assert_eq!(text.android.subtitle, Some("Subtitle"));
+    assert_eq!(text.android.description, None);
+    assert_eq!(text.apple, "authenticate");
+    assert_eq!(text.linux.message, "Unlock the test vault");
+}
+
+#[test]
+fn text_buf_from_strings() {
+    let name = String::from("vault");
+    let text = TextBuf {
+        android: AndroidTextBuf {
+            title: format!("Unlock {name}"),
+            subtitle: None,
+            description: None,
+        },
+        apple: format!("unlock {name}"),
+        windows: WindowsTextBuf::new(format!("Unlock {name}"), "Description").unwrap(),
+        linux: LinuxTextBuf {
+            message: format!("Authentication is required to unlock {name}"),
+        },
+    };
+    let cloned = text.clone();
+
+    assert_eq!(cloned.as_text().android.title, "Unlock vault");
+    assert_eq!(
+        cloned.as_text().linux.message,
+        "Authentication is required to unlock vault"
+    );
+}